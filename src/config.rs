@@ -2,7 +2,16 @@
 // Licensed under the GNU GPL v3 license. See the LICENSE file for the terms and conditions.
 
 use std::env;
+use std::fmt;
+use crate::player::{DEFAULT_CLIENT_WAIT_CYCLES_THRESHOLD, DEFAULT_HOST, DEFAULT_PAUSE_SLEEP_MILLIS, DEFAULT_SOCKET_CONNECTION_TIMEOUT, DEFAULT_WRITE_CYCLES_THRESHOLD, MAX_VOLUME};
+use crate::player::sid_device::{DeviceId, SamplingMethod, SidClock};
+use crate::console_player::AtEnd;
+use crate::utils::{config_file, sidblaster, song_number};
+use crate::utils::song_number::DEFAULT_SONG;
+use crate::utils::term::ColorMode;
+use crate::utils::time_position::TimePosition;
 
+#[derive(Debug)]
 pub struct Config {
     pub hvsc_location: Option<String>,
     pub host_name_sid_device: Option<String>,
@@ -11,50 +20,487 @@ pub struct Config {
     pub display_devices: bool,
     pub adjust_clock: bool,
     pub device_numbers: Vec<i32>,
+    pub enabled_device_types: Option<Vec<DeviceId>>,
+    pub disabled_device_types: Vec<DeviceId>,
+    pub preferred_device_type: Option<DeviceId>,
     pub song_number: i32,
+    pub song_title: Option<String>,
+    pub song_range: Option<(i32, i32)>,
+    pub c64_version: Option<i32>,
+    pub sid_clock: Option<SidClock>,
+    pub sampling_method: Option<SamplingMethod>,
+    pub fade_in: Option<u32>,
+    pub now_playing: bool,
+    pub fast_switch: bool,
+    pub estimate_length: bool,
+    pub balance: i32,
+    pub volume: Option<u8>,
+    pub buffer_meter: bool,
+    pub swap_stereo: bool,
+    pub stats: bool,
+    pub detect_loop: bool,
+    pub stop_at_loop: bool,
+    pub step_mode: bool,
+    pub profile: bool,
+    pub self_test: bool,
+    pub print_config: bool,
+    pub idle_timeout: Option<u32>,
+    pub hs_busy_timeout: Option<u32>,
+    pub reglog_filename: Option<String>,
+    pub error_log_filename: Option<String>,
+    pub play_reglog_filename: Option<String>,
+    pub pause_sleep: Option<u32>,
+    pub connect_timeout: Option<u32>,
+    pub ftdi_latency: Option<u32>,
+    pub skip_empty: Option<u32>,
+    pub color_mode: ColorMode,
+    pub seek_accurate: bool,
+    pub window_title: bool,
+    pub frames: Option<u32>,
+    pub fingerprint_filename: Option<String>,
+    pub max_songs: Option<u32>,
+    pub at_end: Option<AtEnd>,
+    pub hexdump: bool,
+    pub hvsc_path: bool,
+    pub scan_directory: Option<String>,
+    pub timer_boost: bool,
+    pub warn_chip: bool,
+    pub reset_on_exit: bool,
+    pub trace_protocol: bool,
+    pub mono_downmix: bool,
+    pub only_registers: Option<Vec<u8>>,
+    pub tee_host_name: Option<String>,
+    pub sid_variant: Option<String>,
+    pub net_flush_cycles: Option<u32>,
+    pub net_throttle_cycles: Option<u32>,
+    #[cfg(windows)]
+    pub mute_on_unfocus: bool,
+    #[cfg(unix)]
+    pub status_socket: Option<String>,
+    pub clip: Option<(TimePosition, TimePosition)>,
     pub filename: String
 }
 
 impl Config {
     pub fn read() -> Result<Config, String> {
-        let mut hvsc_location = None;
-        let mut host_name_sid_device = None;
-        let mut host_name_ultimate_device = None;
-        let mut display_stil = false;
-        let mut display_devices = false;
-        let mut adjust_clock = false;
-        let mut device_numbers = vec![-1];
-        let mut song_number = -1;
-        let filename = env::args().last().unwrap();
-
-        for argument in env::args().filter(|arg| arg.len() > 1 && arg.starts_with('-')) {
+        let mut config = Config {
+            hvsc_location: None,
+            host_name_sid_device: None,
+            host_name_ultimate_device: None,
+            display_stil: false,
+            display_devices: false,
+            adjust_clock: false,
+            device_numbers: vec![-1],
+            enabled_device_types: None,
+            disabled_device_types: vec![],
+            preferred_device_type: None,
+            song_number: DEFAULT_SONG,
+            song_title: None,
+            song_range: None,
+            c64_version: None,
+            sid_clock: None,
+            sampling_method: None,
+            fade_in: None,
+            now_playing: false,
+            fast_switch: false,
+            estimate_length: false,
+            balance: 0,
+            volume: None,
+            buffer_meter: false,
+            swap_stereo: false,
+            stats: false,
+            detect_loop: false,
+            stop_at_loop: false,
+            step_mode: false,
+            profile: false,
+            self_test: false,
+            print_config: false,
+            idle_timeout: None,
+            hs_busy_timeout: None,
+            reglog_filename: None,
+            error_log_filename: None,
+            play_reglog_filename: None,
+            pause_sleep: None,
+            connect_timeout: None,
+            ftdi_latency: None,
+            skip_empty: None,
+            color_mode: ColorMode::Auto,
+            seek_accurate: false,
+            window_title: false,
+            frames: None,
+            fingerprint_filename: None,
+            max_songs: None,
+            at_end: None,
+            hexdump: false,
+            hvsc_path: false,
+            scan_directory: None,
+            timer_boost: true,
+            warn_chip: true,
+            reset_on_exit: true,
+            trace_protocol: false,
+            mono_downmix: false,
+            only_registers: None,
+            tee_host_name: None,
+            sid_variant: None,
+            net_flush_cycles: None,
+            net_throttle_cycles: None,
+            #[cfg(windows)]
+            mute_on_unfocus: false,
+            #[cfg(unix)]
+            status_socket: None,
+            clip: None,
+            filename: env::args().last().unwrap()
+        };
+
+        let mut arguments = config_file::read_arguments()?.into_iter().chain(env::args().skip(1));
+        while let Some(argument) = arguments.next() {
+            if argument.len() <= 1 || !argument.starts_with('-') {
+                continue;
+            }
+
             match &argument[1..2] {
-                "c" => adjust_clock = true,
-                "d" => device_numbers = Self::parse_argument_numbers("Device number", &argument[2..])?,
+                "-" => config.parse_long_option(&argument[2..], &mut arguments)?,
+                "c" => config.adjust_clock = true,
+                "C" => config.sid_clock = Some(Self::parse_sid_clock(&argument[2..])?),
+                "d" => config.device_numbers = Self::parse_argument_numbers("Device number", &argument[2..])?,
                 "h" => match &argument[2..3] {
-                    "s" => host_name_sid_device = Some(argument[3..].to_string()),
-                    "u" => host_name_ultimate_device = Some(argument[3..].to_string()),
+                    "s" => config.host_name_sid_device = Some(argument[3..].to_string()),
+                    "u" => config.host_name_ultimate_device = Some(argument[3..].to_string()),
                     _ => {}
                 },
-                "i" => display_stil = true,
-                "l" => hvsc_location = Some(argument[2..].to_string()),
-                "p" => display_devices = true,
-                "s" => song_number = Self::parse_argument_number("Song number", &argument[2..])?,
+                "i" => config.display_stil = true,
+                "l" => config.hvsc_location = Some(argument[2..].to_string()),
+                "p" => config.display_devices = true,
+                "s" => {
+                    let value = &argument[2..];
+                    if value.is_empty() || value.parse::<i32>().is_ok() {
+                        config.song_number = Self::parse_song_number("Song number", value)?;
+                    } else {
+                        config.song_title = Some(value.to_string());
+                    }
+                },
                 _ => return Err(format!("Unknown option: {argument}"))
             }
         }
 
-        Ok(Config {
-            hvsc_location,
-            host_name_sid_device,
-            host_name_ultimate_device,
-            display_stil,
-            display_devices,
-            adjust_clock,
-            device_numbers,
-            song_number,
-            filename
-        })
+        Ok(config)
+    }
+
+    fn parse_long_option(&mut self, option: &str, arguments: &mut impl Iterator<Item = String>) -> Result<(), String> {
+        match option {
+            "at-end" => {
+                let value = Self::next_value(option, arguments)?;
+                self.at_end = Some(Self::parse_at_end(&value)?);
+            },
+            "balance" => {
+                let value = Self::next_value(option, arguments)?;
+                self.balance = Self::parse_balance(&value)?;
+            },
+            "c64-version" => {
+                let value = Self::next_value(option, arguments)?;
+                self.c64_version = Some(Self::parse_c64_version(&value)?);
+            },
+            "clip" => {
+                let value = Self::next_value(option, arguments)?;
+                self.clip = Some(Self::parse_clip(&value)?);
+            },
+            "color" => {
+                let value = Self::next_value(option, arguments)?;
+                self.color_mode = Self::parse_color_mode(&value)?;
+            },
+            "connect-timeout" => {
+                let value = Self::next_value(option, arguments)?;
+                self.connect_timeout = Some(Self::parse_number("Connect timeout", &value)?);
+            },
+            "detect-loop" => {
+                let value = Self::next_value(option, arguments)?;
+                self.detect_loop = true;
+                self.stop_at_loop = Self::parse_stop_at_loop(&value)?;
+            },
+            "disable" => {
+                let value = Self::next_value(option, arguments)?;
+                self.disabled_device_types = Self::parse_device_types(&value)?;
+            },
+            "enable" => {
+                let value = Self::next_value(option, arguments)?;
+                self.enabled_device_types = Some(Self::parse_device_types(&value)?);
+            },
+            "error-log" => self.error_log_filename = Some(Self::next_value(option, arguments)?),
+            "fade-in" => {
+                let value = Self::next_value(option, arguments)?;
+                self.fade_in = Some(Self::parse_number("Fade in", &value)?);
+            },
+            "fingerprint" => {
+                let value = Self::next_value(option, arguments)?;
+                self.fingerprint_filename = Some(Self::parse_fingerprint_filename(&value)?);
+            },
+            "frames" => {
+                let value = Self::next_value(option, arguments)?;
+                self.frames = Some(Self::parse_positive_number("Frames", &value)?);
+            },
+            "ftdi-latency" => {
+                let value = Self::next_value(option, arguments)?;
+                self.ftdi_latency = Some(Self::parse_ftdi_latency(&value)?);
+            },
+            "hs-busy-timeout" => {
+                let value = Self::next_value(option, arguments)?;
+                self.hs_busy_timeout = Some(Self::parse_positive_number("HardSID busy timeout", &value)?);
+            },
+            "idle-timeout" => {
+                let value = Self::next_value(option, arguments)?;
+                self.idle_timeout = Some(Self::parse_positive_number("Idle timeout", &value)?);
+            },
+            "max-songs" => {
+                let value = Self::next_value(option, arguments)?;
+                self.max_songs = Some(Self::parse_positive_number("Max songs", &value)?);
+            },
+            "net-flush-cycles" => {
+                let value = Self::next_value(option, arguments)?;
+                self.net_flush_cycles = Some(Self::parse_positive_number("Network flush cycles", &value)?);
+            },
+            "net-throttle-cycles" => {
+                let value = Self::next_value(option, arguments)?;
+                self.net_throttle_cycles = Some(Self::parse_positive_number("Network throttle cycles", &value)?);
+            },
+            "only-regs" => {
+                let value = Self::next_value(option, arguments)?;
+                self.only_registers = Some(Self::parse_registers(&value)?);
+            },
+            "pause-sleep" => {
+                let value = Self::next_value(option, arguments)?;
+                self.pause_sleep = Some(Self::parse_positive_number("Pause sleep", &value)?);
+            },
+            "playreglog" => self.play_reglog_filename = Some(Self::next_value(option, arguments)?),
+            "prefer" => {
+                let value = Self::next_value(option, arguments)?;
+                self.preferred_device_type = Some(Self::parse_device_type(&value)?);
+            },
+            "quality" => {
+                let value = Self::next_value(option, arguments)?;
+                self.sampling_method = Some(Self::parse_sampling_method(&value)?);
+            },
+            "reglog" => self.reglog_filename = Some(Self::next_value(option, arguments)?),
+            "scan" => self.scan_directory = Some(Self::next_value(option, arguments)?),
+            "sid-variant" => self.sid_variant = Some(Self::next_value(option, arguments)?),
+            "skip-empty" => {
+                let value = Self::next_value(option, arguments)?;
+                self.skip_empty = Some(Self::parse_positive_number("Skip empty", &value)?);
+            },
+            "songs" => {
+                let value = Self::next_value(option, arguments)?;
+                self.song_range = Some(Self::parse_song_range(&value)?);
+            },
+            #[cfg(unix)]
+            "status-socket" => self.status_socket = Some(Self::next_value(option, arguments)?),
+            "tee" => self.tee_host_name = Some(Self::next_value(option, arguments)?),
+            "volume" => {
+                let value = Self::next_value(option, arguments)?;
+                self.volume = Some(Self::parse_volume(&value)?);
+            },
+            _ => {
+                let (flag, enabled) = option.strip_prefix("no-").map_or((option, true), |flag| (flag, false));
+                if !self.set_flag(flag, enabled) {
+                    return Err(format!("Unknown option: --{option}"));
+                }
+            }
+        }
+        Ok(())
+    }
+
+    // every flag can be turned off with a --no- prefix, so a flag enabled in the config file can be disabled on the command line
+    fn set_flag(&mut self, flag: &str, enabled: bool) -> bool {
+        let value = match flag {
+            "adjust-clock" => &mut self.adjust_clock,
+            "buffer-meter" => &mut self.buffer_meter,
+            "estimate-length" => &mut self.estimate_length,
+            "fast-switch" => &mut self.fast_switch,
+            "hexdump" => &mut self.hexdump,
+            "hvsc-path" => &mut self.hvsc_path,
+            "mono-downmix" => &mut self.mono_downmix,
+            #[cfg(windows)]
+            "mute-on-unfocus" => &mut self.mute_on_unfocus,
+            "nowplaying" => &mut self.now_playing,
+            "print-config" => &mut self.print_config,
+            "profile" => &mut self.profile,
+            "reset-on-exit" => &mut self.reset_on_exit,
+            "seek-accurate" => &mut self.seek_accurate,
+            "selftest" => &mut self.self_test,
+            "set-title" => &mut self.window_title,
+            "stats" => &mut self.stats,
+            "step" => &mut self.step_mode,
+            "swap-stereo" => &mut self.swap_stereo,
+            "timer-boost" => &mut self.timer_boost,
+            "trace-protocol" => &mut self.trace_protocol,
+            "warn-chip" => &mut self.warn_chip,
+            _ => return false
+        };
+        *value = enabled;
+        true
+    }
+
+    fn next_value(option: &str, arguments: &mut impl Iterator<Item = String>) -> Result<String, String> {
+        arguments.next().ok_or(format!("Option --{option} requires a value."))
+    }
+
+    fn parse_fingerprint_filename(arg_value: &str) -> Result<String, String> {
+        if arg_value.to_lowercase().ends_with(".svg") {
+            Ok(arg_value.to_string())
+        } else {
+            Err("Fingerprint file name must have the extension .svg.".to_string())
+        }
+    }
+
+    fn parse_volume(arg_value: &str) -> Result<u8, String> {
+        match arg_value.parse::<u8>() {
+            Ok(volume) if volume <= MAX_VOLUME => Ok(volume),
+            _ => Err(format!("Volume must be between 0 and {MAX_VOLUME}."))
+        }
+    }
+
+    fn parse_c64_version(arg_value: &str) -> Result<i32, String> {
+        match arg_value.parse::<i32>() {
+            Ok(c64_version) if (0..=3).contains(&c64_version) => Ok(c64_version),
+            _ => Err("C64 version must be 0 (unknown), 1 (PAL), 2 (NTSC) or 3 (PAL/NTSC).".to_string())
+        }
+    }
+
+    fn parse_sid_clock(arg_value: &str) -> Result<SidClock, String> {
+        match arg_value.to_ascii_lowercase().as_str() {
+            "pal" => Ok(SidClock::Pal),
+            "ntsc" => Ok(SidClock::Ntsc),
+            "ntsc-old" => Ok(SidClock::NtscOld),
+            "drean" => Ok(SidClock::Drean),
+            _ => Err("Clock must be pal, ntsc, ntsc-old or drean.".to_string())
+        }
+    }
+
+    fn parse_at_end(arg_value: &str) -> Result<AtEnd, String> {
+        match arg_value {
+            "stop" => Ok(AtEnd::Stop),
+            "loop" => Ok(AtEnd::Loop),
+            "next-file" => Ok(AtEnd::NextFile),
+            "quit" => Ok(AtEnd::Quit),
+            _ => Err("At end must be stop, loop, next-file or quit.".to_string())
+        }
+    }
+
+    fn parse_stop_at_loop(arg_value: &str) -> Result<bool, String> {
+        match arg_value {
+            "report" => Ok(false),
+            "stop" => Ok(true),
+            _ => Err("Detect loop must be report or stop.".to_string())
+        }
+    }
+
+    fn parse_device_types(arg_value: &str) -> Result<Vec<DeviceId>, String> {
+        arg_value
+            .split(',')
+            .map(Self::parse_device_type)
+            .collect()
+    }
+
+    fn parse_device_type(arg_value: &str) -> Result<DeviceId, String> {
+        match arg_value.trim() {
+            "hardsid" => Ok(DeviceId::HardsidUsb),
+            "sidblaster" => Ok(DeviceId::SidBlaster),
+            "network" => Ok(DeviceId::NetworkSidDevice),
+            "ultimate" => Ok(DeviceId::UltimateDevice),
+            _ => Err(format!("Unknown device type '{arg_value}', must be hardsid, sidblaster, network or ultimate."))
+        }
+    }
+
+    fn parse_sampling_method(arg_value: &str) -> Result<SamplingMethod, String> {
+        match arg_value {
+            "fast" => Ok(SamplingMethod::Fast),
+            "accurate" => Ok(SamplingMethod::Best),
+            _ => Err("Quality must be fast or accurate.".to_string())
+        }
+    }
+
+    fn parse_color_mode(arg_value: &str) -> Result<ColorMode, String> {
+        match arg_value {
+            "auto" => Ok(ColorMode::Auto),
+            "always" => Ok(ColorMode::Always),
+            "never" => Ok(ColorMode::Never),
+            _ => Err("Color must be auto, always or never.".to_string())
+        }
+    }
+
+    fn parse_balance(arg_value: &str) -> Result<i32, String> {
+        match arg_value.parse::<i32>() {
+            Ok(balance) if (-100..=100).contains(&balance) => Ok(balance),
+            _ => Err("Balance must be a number between -100 and 100.".to_string())
+        }
+    }
+
+    fn parse_ftdi_latency(arg_value: &str) -> Result<u32, String> {
+        match arg_value.parse::<u32>() {
+            Ok(latency) if (1..=255).contains(&latency) => Ok(latency),
+            _ => Err("FTDI latency must be a number between 1 and 255.".to_string())
+        }
+    }
+
+    fn parse_clip(arg_value: &str) -> Result<(TimePosition, TimePosition), String> {
+        let clip = arg_value.get(1..)
+            .and_then(|value| value.find('-'))
+            .and_then(|separator| Some((TimePosition::parse(&arg_value[..=separator])?, TimePosition::parse(&arg_value[separator + 2..])?)));
+
+        match clip {
+            Some((TimePosition::Absolute(start), TimePosition::Absolute(end))) if start >= end => Err("Clip start time must be before the end time.".to_string()),
+            Some(clip) => Ok(clip),
+            None => Err("Clip must be specified as start-end, with each time as mm:ss, a percentage like 50% or a time before the end like -0:10.".to_string())
+        }
+    }
+
+    fn parse_number(arg_name: &str, arg_value: &str) -> Result<u32, String> {
+        arg_value.parse::<u32>().map_err(|_| format!("{arg_name} must be a valid number."))
+    }
+
+    fn parse_registers(arg_value: &str) -> Result<Vec<u8>, String> {
+        let mut registers = vec![];
+
+        for register_range in arg_value.split(',') {
+            let (first_register, last_register) = register_range.split_once('-').unwrap_or((register_range, register_range));
+            let first_register = Self::parse_register(first_register)?;
+            let last_register = Self::parse_register(last_register)?;
+
+            if first_register > last_register {
+                return Err(format!("Register range {register_range} must start with the lowest register."));
+            }
+            registers.extend(first_register..=last_register);
+        }
+        Ok(registers)
+    }
+
+    fn parse_register(arg_value: &str) -> Result<u8, String> {
+        let register = arg_value.trim();
+        let register = register.strip_prefix('$').unwrap_or(register);
+
+        u8::from_str_radix(register, 16).map_err(|_| format!("Register {arg_value} must be a hexadecimal number between 00 and ff."))
+    }
+
+    fn parse_positive_number(arg_name: &str, arg_value: &str) -> Result<u32, String> {
+        match arg_value.parse::<u32>() {
+            Ok(number) if number > 0 => Ok(number),
+            _ => Err(format!("{arg_name} must be a valid number and must be higher than 0."))
+        }
+    }
+
+    fn parse_song_range(arg_value: &str) -> Result<(i32, i32), String> {
+        let (first_song, last_song) = arg_value.split_once('-').ok_or("Songs must be specified as first-last.")?;
+        let first_song = Self::parse_song_number("First song", first_song)?;
+        let last_song = Self::parse_song_number("Last song", last_song)?;
+
+        if first_song > last_song {
+            return Err("First song must not be higher than the last song.".to_string());
+        }
+        Ok((first_song, last_song))
+    }
+
+    fn parse_song_number(arg_name: &str, arg_value: &str) -> Result<i32, String> {
+        arg_value.parse::<i32>()
+            .map_err(|_| format!("{arg_name} must be a valid number and must be higher than 0."))
+            .and_then(|number| song_number::from_user_song_number(number).ok_or(format!("{arg_name} must be higher than 0.")))
     }
 
     fn parse_argument_numbers(arg_name: &str, arg_values: &str) -> Result<Vec<i32>, String> {
@@ -75,4 +521,102 @@ impl Config {
                 }
             })
     }
+
+    fn write_setting(f: &mut fmt::Formatter, name: &str, value: &str) -> fmt::Result {
+        writeln!(f, "  {name:<20}: {value}")
+    }
+
+    fn on_off(enabled: bool) -> &'static str {
+        if enabled { "on" } else { "off" }
+    }
+
+    fn or_none(value: String) -> String {
+        if value.is_empty() { "none".to_string() } else { value }
+    }
+}
+
+// shows the settings as they are used, with the defaults applied and the song and device numbers 1-based
+impl fmt::Display for Config {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let device_numbers = self.device_numbers.iter()
+            .map(|&device_number| if device_number == -1 { "default".to_string() } else { (device_number + 1).to_string() })
+            .collect::<Vec<String>>()
+            .join(",");
+        let song = if self.song_number == DEFAULT_SONG {
+            "start song of the file".to_string()
+        } else {
+            song_number::to_user_song_number(self.song_number).to_string()
+        };
+        let song_range = self.song_range.map(|(first_song, last_song)|
+            format!("{}-{}", song_number::to_user_song_number(first_song), song_number::to_user_song_number(last_song)));
+        let device_types = |device_types: &[DeviceId]| device_types.iter().map(|device_type| format!("{device_type:?}")).collect::<Vec<String>>().join(",");
+
+        writeln!(f, "Settings:")?;
+        Self::write_setting(f, "file", &self.filename)?;
+        Self::write_setting(f, "song", &self.song_title.as_ref().map_or(song, |song_title| format!("first song containing '{song_title}'")))?;
+        Self::write_setting(f, "songs", &song_range.unwrap_or("all".to_string()))?;
+        Self::write_setting(f, "hvsc location", self.hvsc_location.as_deref().unwrap_or("detected from the file"))?;
+        Self::write_setting(f, "device numbers", &device_numbers)?;
+        Self::write_setting(f, "enabled devices", &self.enabled_device_types.as_deref().map_or("all".to_string(), device_types))?;
+        Self::write_setting(f, "disabled devices", &Self::or_none(device_types(&self.disabled_device_types)))?;
+        Self::write_setting(f, "preferred device", &self.preferred_device_type.map_or("none".to_string(), |device_type| format!("{device_type:?}")))?;
+        Self::write_setting(f, "network host", self.host_name_sid_device.as_deref().unwrap_or(DEFAULT_HOST))?;
+        Self::write_setting(f, "ultimate host", self.host_name_ultimate_device.as_deref().unwrap_or("none"))?;
+        Self::write_setting(f, "tee host", self.tee_host_name.as_deref().unwrap_or("none"))?;
+        Self::write_setting(f, "sid variant", self.sid_variant.as_deref().unwrap_or("none"))?;
+        Self::write_setting(f, "connect timeout", &format!("{} ms", self.connect_timeout.map_or(DEFAULT_SOCKET_CONNECTION_TIMEOUT, |timeout| timeout as u64)))?;
+        Self::write_setting(f, "net flush cycles", &self.net_flush_cycles.unwrap_or(DEFAULT_WRITE_CYCLES_THRESHOLD).to_string())?;
+        Self::write_setting(f, "net throttle cycles", &self.net_throttle_cycles.unwrap_or(DEFAULT_CLIENT_WAIT_CYCLES_THRESHOLD).to_string())?;
+        Self::write_setting(f, "ftdi latency", &format!("{} ms", self.ftdi_latency.map_or(sidblaster::DEFAULT_LATENCY_IN_MILLIS, |latency| latency as u64)))?;
+        Self::write_setting(f, "hs busy timeout", &self.hs_busy_timeout.map_or("none".to_string(), |timeout| format!("{timeout} ms")))?;
+        Self::write_setting(f, "adjust clock", Self::on_off(self.adjust_clock))?;
+        Self::write_setting(f, "sid clock", &self.sid_clock.map_or("clock of the tune".to_string(), |sid_clock| format!("{sid_clock:?}")))?;
+        Self::write_setting(f, "c64 version", &self.c64_version.map_or("version of the tune".to_string(), |c64_version| c64_version.to_string()))?;
+        Self::write_setting(f, "quality", &self.sampling_method.map_or("accurate".to_string(), |sampling_method| format!("{sampling_method:?}")))?;
+        Self::write_setting(f, "fade in", &self.fade_in.map_or("off".to_string(), |fade_in| format!("{fade_in} ms")))?;
+        Self::write_setting(f, "balance", &self.balance.to_string())?;
+        Self::write_setting(f, "volume", &self.volume.unwrap_or(MAX_VOLUME).to_string())?;
+        Self::write_setting(f, "only registers", &self.only_registers.as_ref().map_or("all".to_string(), |registers|
+            registers.iter().map(|register| format!("{register:02x}")).collect::<Vec<String>>().join(",")))?;
+        Self::write_setting(f, "clip", &self.clip.map_or("none".to_string(), |(start_time, end_time)| format!("{start_time:?} - {end_time:?}")))?;
+        Self::write_setting(f, "at end", &self.at_end.map_or("keep playing".to_string(), |at_end| format!("{at_end:?}")))?;
+        Self::write_setting(f, "max songs", &self.max_songs.map_or("none".to_string(), |max_songs| max_songs.to_string()))?;
+        Self::write_setting(f, "idle timeout", &self.idle_timeout.map_or("none".to_string(), |timeout| format!("{timeout} s")))?;
+        Self::write_setting(f, "skip empty", &self.skip_empty.map_or("off".to_string(), |skip_empty| format!("{skip_empty} s")))?;
+        Self::write_setting(f, "detect loop", &if self.detect_loop { format!("on, stop at loop {}", Self::on_off(self.stop_at_loop)) } else { "off".to_string() })?;
+        Self::write_setting(f, "pause sleep", &format!("{} ms", self.pause_sleep.map_or(DEFAULT_PAUSE_SLEEP_MILLIS, |pause_sleep| pause_sleep as u64)))?;
+        Self::write_setting(f, "color", &format!("{:?}", self.color_mode))?;
+        Self::write_setting(f, "error log", self.error_log_filename.as_deref().unwrap_or("none"))?;
+        Self::write_setting(f, "register log", self.reglog_filename.as_deref().unwrap_or("none"))?;
+        #[cfg(unix)]
+        Self::write_setting(f, "status socket", self.status_socket.as_deref().unwrap_or("none"))?;
+
+        let flags = [
+            ("stil", self.display_stil),
+            ("nowplaying", self.now_playing),
+            ("fast switch", self.fast_switch),
+            ("estimate length", self.estimate_length),
+            ("buffer meter", self.buffer_meter),
+            ("swap stereo", self.swap_stereo),
+            ("mono downmix", self.mono_downmix),
+            ("stats", self.stats),
+            ("step", self.step_mode),
+            ("profile", self.profile),
+            ("seek accurate", self.seek_accurate),
+            ("set title", self.window_title),
+            ("hexdump", self.hexdump),
+            ("hvsc path", self.hvsc_path),
+            ("timer boost", self.timer_boost),
+            ("warn chip", self.warn_chip),
+            ("reset on exit", self.reset_on_exit),
+            ("trace protocol", self.trace_protocol)
+        ];
+
+        for (name, enabled) in flags {
+            Self::write_setting(f, name, Self::on_off(enabled))?;
+        }
+        #[cfg(windows)]
+        Self::write_setting(f, "mute on unfocus", Self::on_off(self.mute_on_unfocus))?;
+        Ok(())
+    }
 }
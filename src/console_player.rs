@@ -3,13 +3,21 @@
 
 mod clock;
 
-use crate::player::{Player, PlayerCommand, ABORT_NO, ABORT_TO_QUIT, ABORT_FOR_COMMAND, PlayerOutput, ABORTED};
-use crate::utils::keyboard;
+use crate::player::{DetectedLoop, HvscDatabases, Player, PlayerCommand, PlayerQuery, PlayerState, MUS_TEXT_LINE_LENGTH, MAX_VOLUME, ABORT_NO, ABORT_TO_QUIT, ABORT_FOR_COMMAND, PlayerOutput, ABORTED};
+use crate::utils::{error_log, file, keyboard, petscii, sid_file, song_number, term};
+use crate::utils::song_number::DEFAULT_SONG;
+#[cfg(unix)]
+use crate::utils::json;
+#[cfg(unix)]
+use crate::utils::status_socket::StatusSocket;
 use self::clock::Clock;
 
-use std::path::Path;
+use std::fmt::Write as _;
+use std::io::{stdout, Write};
+use std::{fs, process};
+use std::path::{Path, PathBuf};
 use std::sync::atomic::{AtomicBool, AtomicI32, Ordering};
-use std::sync::mpsc::SyncSender;
+use std::sync::mpsc::{sync_channel, SyncSender};
 use std::sync::Arc;
 use std::{thread, time::Duration};
 use std::time::Instant;
@@ -17,16 +25,69 @@ use parking_lot::Mutex;
 
 const LOOP_RATE_IN_MS: u64 = 50;
 const FAST_FORWARD_STOP_DELAY_IN_MILLIS: u128 = 600;
+const STOP_PLAYER_TIMEOUT_IN_MILLIS: u128 = 5000;
+const PLAYER_QUERY_TIMEOUT_IN_MILLIS: u64 = 500;
+
+#[derive(Copy, Clone, Debug, Eq, PartialEq)]
+pub enum AtEnd {
+    Stop,
+    Loop,
+    NextFile,
+    Quit
+}
+
+#[derive(Default)]
+struct InfoBlock {
+    info: String,
+    stil_entry: Option<String>,
+    device_info: String,
+    clock_display: String,
+    song_length: Option<i32>
+}
 
 pub struct ConsolePlayer {
     player: Arc<Mutex<Player>>,
     player_cmd_sender: SyncSender<PlayerCommand>,
+    player_query_sender: SyncSender<PlayerQuery>,
     display_stil: bool,
     paused: bool,
     abort_type: Arc<AtomicI32>,
     fast_forward_in_progress: Arc<AtomicBool>,
     last_fast_forward: Arc<Mutex<Instant>>,
     player_output: Arc<Mutex<PlayerOutput>>,
+    now_playing: bool,
+    warn_chip: bool,
+    step_mode: bool,
+    buffer_meter: bool,
+    window_title: bool,
+    volume_fix: bool,
+    volume: u8,
+    now_playing_info: (String, String),
+    now_playing_seconds: Option<usize>,
+    clip: Option<(u32, u32)>,
+    info: InfoBlock,
+    player_has_stil: bool,
+    digi_detected: Arc<AtomicBool>,
+    digi_warning_shown: bool,
+    balance_applied: bool,
+    delay_clamped: Arc<AtomicBool>,
+    played_songs: Vec<(String, i32)>,
+    max_songs: Option<u32>,
+    at_end: Option<AtEnd>,
+    song_end_time: Option<u32>,
+    known_song_end_time: Option<u32>,
+    song_empty: Arc<AtomicBool>,
+    exiting: Arc<AtomicBool>,
+    detected_loop: Arc<Mutex<Option<DetectedLoop>>>,
+    hvsc_root: Option<String>,
+    #[cfg(windows)]
+    mute_on_unfocus: bool,
+    #[cfg(unix)]
+    status_socket: Option<StatusSocket>,
+    #[cfg(unix)]
+    status_info: String,
+    #[cfg(unix)]
+    status_update: Option<(usize, bool)>
 }
 
 impl ConsolePlayer {
@@ -35,32 +96,149 @@ impl ConsolePlayer {
         let last_fast_forward = Arc::new(Mutex::new(Instant::now()));
 
         let player_cmd_sender = player.get_channel_sender();
+        let player_query_sender = player.get_query_sender();
         let player_arc = Arc::new(Mutex::new(player));
         let player_output = player_arc.lock().get_player_output();
         let abort_type = player_arc.lock().get_aborted_ref();
+        let player_has_stil = player_arc.lock().is_stil_loaded();
+        let digi_detected = player_arc.lock().get_digi_detected_ref();
+        let song_empty = player_arc.lock().get_song_empty_ref();
+        let exiting = player_arc.lock().get_exiting_ref();
+        let delay_clamped = player_arc.lock().get_delay_clamped_ref();
+        let detected_loop = player_arc.lock().get_detected_loop_ref();
+        let hvsc_root = player_arc.lock().get_hvsc_root();
+        let volume = player_arc.lock().get_volume();
 
         ConsolePlayer {
             player: player_arc,
             player_cmd_sender,
+            player_query_sender,
             display_stil,
             paused: false,
             abort_type,
             fast_forward_in_progress,
             last_fast_forward,
-            player_output
+            player_output,
+            now_playing: false,
+            warn_chip: true,
+            step_mode: false,
+            buffer_meter: false,
+            window_title: false,
+            volume_fix: true,
+            volume,
+            now_playing_info: (String::new(), String::new()),
+            now_playing_seconds: None,
+            clip: None,
+            info: InfoBlock::default(),
+            player_has_stil,
+            digi_detected,
+            digi_warning_shown: false,
+            balance_applied: false,
+            delay_clamped,
+            played_songs: vec![],
+            max_songs: None,
+            at_end: None,
+            song_end_time: None,
+            known_song_end_time: None,
+            song_empty,
+            exiting,
+            detected_loop,
+            hvsc_root,
+            #[cfg(windows)]
+            mute_on_unfocus: false,
+            #[cfg(unix)]
+            status_socket: None,
+            #[cfg(unix)]
+            status_info: String::new(),
+            #[cfg(unix)]
+            status_update: None
         }
     }
 
+    pub fn set_now_playing(&mut self, now_playing: bool) {
+        self.now_playing = now_playing;
+    }
+
+    pub fn set_step_mode(&mut self, step_mode: bool) {
+        self.step_mode = step_mode;
+    }
+
+    pub fn set_buffer_meter(&mut self, buffer_meter: bool) {
+        self.buffer_meter = buffer_meter;
+    }
+
+    pub fn set_window_title(&mut self, window_title: bool) {
+        self.window_title = window_title;
+    }
+
+    #[cfg(windows)]
+    pub fn set_mute_on_unfocus(&mut self, mute_on_unfocus: bool) {
+        self.mute_on_unfocus = mute_on_unfocus;
+    }
+
+    #[cfg(unix)]
+    pub fn set_status_socket(&mut self, status_socket: StatusSocket) {
+        self.status_socket = Some(status_socket);
+    }
+
+    pub fn set_warn_chip(&mut self, warn_chip: bool) {
+        self.warn_chip = warn_chip;
+    }
+
+    pub fn set_at_end(&mut self, at_end: AtEnd) {
+        self.at_end = Some(at_end);
+    }
+
+    pub fn set_max_songs(&mut self, max_songs: u32) {
+        self.max_songs = Some(max_songs);
+    }
+
+    pub fn get_register_stats_report(&self) -> Option<String> {
+        self.player.lock().get_register_stats_report()
+    }
+
+    pub fn set_clip(&mut self, start_time_in_millis: u32, end_time_in_millis: u32) {
+        self.clip = Some((start_time_in_millis, end_time_in_millis));
+    }
+
     pub fn play(&mut self) -> Result<(), String> {
-        self.print_info();
+        let session_start = Instant::now();
+        self.update_window_title();
 
-        let mut clock = self.setup_and_display_clock();
+        let mut clock = if self.now_playing {
+            self.update_now_playing_info();
+            let mut clock = Clock::new();
+            clock.set_visible(false);
+            clock
+        } else {
+            self.print_info();
+            self.setup_and_display_clock()
+        };
         clock.start();
 
+        #[cfg(unix)]
+        self.update_status_info();
+
         let remote_sidplayer_active = self.player.lock().has_remote_sidplayer();
-        let number_of_tunes = self.player.lock().get_number_of_songs();
+        let mut song_range = self.player.lock().get_song_range();
+        let limited_digi_support = self.player.lock().has_limited_digi_support();
+        self.balance_applied = self.player.lock().is_balance_applied();
+
+        if let Some((start_time, _)) = self.clip {
+            if start_time > 0 {
+                self.player.lock().set_seek_time(start_time);
+            }
+        }
+
         let mut player_thread = self.start_player(&mut clock);
 
+        if let Some((start_time, _)) = self.clip {
+            clock.set_clock(start_time as usize);
+        }
+
+        #[cfg(windows)]
+        let mut muted = false;
+
         self.paused = false;
         loop {
             if let Some(key) = keyboard::get_char_from_input() {
@@ -79,11 +257,12 @@ impl ConsolePlayer {
                             }
                         }
                     },
-                    '0' ..= '9' | '+' | '=' | '-' | '_' => {
+                    '0' ..= '9' | '+' | '=' | '-' | '_' | '*' => {
                         let mut song_number = keyboard::convert_num_key_to_number(key);
-                        let invalid_song_nr = song_number != -1 && number_of_tunes - 1 < song_number;
+                        let invalid_song_nr = song_number != DEFAULT_SONG && !(song_range.0..=song_range.1).contains(&song_number);
 
-                        if !invalid_song_nr || song_number == -1 {
+                        if !invalid_song_nr {
+                            self.clip = None;
                             self.stop_player(player_thread);
                             song_number = match key {
                                 '+' | '=' => self.player.lock().get_next_song(),
@@ -108,36 +287,226 @@ impl ConsolePlayer {
                             continue;
                         }
                     },
+                    ']' | '[' => {
+                        player_thread = self.play_file_in_directory(player_thread, key == ']', &mut clock)?;
+                        song_range = self.player.lock().get_song_range();
+                    },
+                    'r' | 'R' => {
+                        self.clip = None;
+                        self.restart_tune(&mut clock);
+                    },
+                    'f' | 'F' if !remote_sidplayer_active => self.toggle_volume_fix(&mut clock),
+                    '<' | ',' if !remote_sidplayer_active => self.change_volume(-1, &mut clock),
+                    '>' | '.' if !remote_sidplayer_active => self.change_volume(1, &mut clock),
+                    ' ' if self.step_mode && !remote_sidplayer_active => self.step_frame(&mut clock),
+                    'i' | 'I' => self.toggle_stil_info(&mut clock),
+                    'l' | 'L' => self.reload_hvsc_databases(&mut clock),
+                    's' | 'S' => self.print_player_state(&mut clock),
                     keyboard::ESC_KEY => break,
                     _ => ()
                 };
             }
 
-            if self.fast_forward_in_progress.load(Ordering::SeqCst) {
+            if self.fast_forward_in_progress.load(Ordering::SeqCst) || (self.step_mode && self.paused) {
                 let player_output = self.get_player_output();
                 clock.set_clock(player_output.time as usize);
             }
 
+            #[cfg(windows)]
+            if self.mute_on_unfocus && !remote_sidplayer_active {
+                let focused = Self::is_console_focused();
+                if focused == muted {
+                    muted = !focused;
+                    self.send_command(if muted { PlayerCommand::Mute } else { PlayerCommand::Unmute });
+                }
+            }
+
+            if (limited_digi_support || self.balance_applied) && !self.digi_warning_shown && self.digi_detected.load(Ordering::Relaxed) {
+                self.print_digi_warning(limited_digi_support, &mut clock);
+            }
+
+            if self.delay_clamped.swap(false, Ordering::Relaxed) {
+                self.print_warning("Warning: an unrealistic delay between SID writes was limited to 5 seconds.", &mut clock);
+            }
+
+            let detected_loop = self.detected_loop.lock().take();
+            if let Some(detected_loop) = detected_loop {
+                self.print_detected_loop(&detected_loop, &mut clock);
+            }
+
+            if self.buffer_meter {
+                clock.set_buffer_fill(self.get_player_output().buffer_fill);
+            }
+
             clock.refresh_clock();
 
-            if self.is_aborted() {
+            if self.now_playing {
+                self.print_now_playing(&clock);
+            }
+
+            #[cfg(unix)]
+            self.send_status(&clock);
+
+            if self.clip.is_some_and(|(_, end_time)| clock.get_clock() >= end_time as usize) {
+                break;
+            }
+
+            if self.song_end_time.is_some_and(|end_time| clock.get_clock() >= end_time as usize) {
+                self.song_end_time = None;
+
+                match self.at_end {
+                    Some(AtEnd::Stop) => {
+                        self.disable_fast_forward(&mut clock);
+                        self.pause_tune();
+                        clock.pause(true);
+                    },
+                    Some(AtEnd::Loop) | Some(AtEnd::NextFile) => {
+                        if self.is_song_limit_reached() {
+                            break;
+                        }
+
+                        if self.at_end == Some(AtEnd::Loop) {
+                            self.stop_player(player_thread);
+                            let song_number = self.player.lock().get_song_number();
+                            self.player.lock().set_song_to_play(song_number)?;
+                            self.refresh_info(&mut clock);
+                            player_thread = self.start_player(&mut clock);
+                        } else {
+                            player_thread = self.play_file_in_directory(player_thread, true, &mut clock)?;
+                            song_range = self.player.lock().get_song_range();
+                        }
+                        continue;
+                    },
+                    _ => break
+                }
+            }
+
+            // the player flags an empty song before it aborts, so read the abort state first to not miss the flag
+            let aborted = self.is_aborted();
+
+            if self.song_empty.swap(false, Ordering::SeqCst) {
+                let next_song_number = self.get_next_non_silent_song();
+                if next_song_number > song_range.1 || self.is_song_limit_reached() {
+                    break;
+                }
+
+                self.clip = None;
+                self.stop_player(player_thread);
+                self.player.lock().set_song_to_play(next_song_number)?;
+                self.refresh_info(&mut clock);
+                player_thread = self.start_player(&mut clock);
+                continue;
+            }
+
+            if aborted {
                 break;
             }
             thread::sleep(Duration::from_millis(LOOP_RATE_IN_MS));
         }
 
         clock.stop();
+        self.exiting.store(true, Ordering::SeqCst);
         self.stop_player(player_thread);
         self.player.lock().stop_player();
 
+        if self.window_title {
+            term::set_window_title("");
+        }
+
+        let detected_loop = self.detected_loop.lock().take();
+        if let Some(detected_loop) = detected_loop {
+            let message = Self::get_detected_loop_message(&detected_loop);
+            if self.now_playing {
+                eprintln!("{message}");
+            } else {
+                print!("\n\n{message}");
+            }
+        }
+
         let last_error = self.player.lock().get_last_error();
         if let Some(last_error) = last_error {
-            println!("\n\nERROR: {last_error}\nExiting!");
+            if self.now_playing {
+                eprintln!("ERROR: {last_error}");
+            } else {
+                println!("\n\nERROR: {last_error}\nExiting!");
+            }
+        } else if !self.now_playing {
+            self.print_summary(&session_start);
         }
 
         Ok(())
     }
 
+    fn print_summary(&self, session_start: &Instant) {
+        let play_time = Clock::convert_seconds_to_time_string(session_start.elapsed().as_secs() as u32, true);
+
+        let mut played_files = vec![];
+        for (filename, _) in &self.played_songs {
+            if !played_files.contains(&filename) {
+                played_files.push(filename);
+            }
+        }
+
+        if played_files.len() > 1 {
+            println!("\n\nPlayed {} song(s) of {} files in {play_time}", self.played_songs.len(), played_files.len());
+            return;
+        }
+
+        let filename = self.player.lock().get_filename().unwrap_or_default();
+        let filename = Path::new(&filename).file_name().map(|name| name.to_string_lossy().to_string()).unwrap_or_default();
+        let songs = self.played_songs.iter().map(|(_, song_number)| song_number::to_user_song_number(*song_number).to_string()).collect::<Vec<String>>().join(", ");
+
+        println!("\n\nPlayed {} song(s) of 1 file in {play_time}: {filename} (songs {songs})", self.played_songs.len());
+    }
+
+    fn play_file_in_directory(&mut self, player_thread: thread::JoinHandle<()>, forward: bool, clock: &mut Clock) -> Result<thread::JoinHandle<()>, String> {
+        self.clip = None;
+        self.stop_player(player_thread);
+        self.player.lock().stop_player();
+
+        self.load_file_in_directory(forward)?;
+        self.digi_warning_shown = false;
+        self.balance_applied = self.player.lock().is_balance_applied();
+
+        self.refresh_info(clock);
+        Ok(self.start_player(clock))
+    }
+
+    fn load_file_in_directory(&mut self, forward: bool) -> Result<(), String> {
+        let filename = PathBuf::from(self.player.lock().get_filename().unwrap_or_default());
+        let files = filename.parent()
+            .map(file::get_sorted_files_in_directory)
+            .unwrap_or_default()
+            .into_iter()
+            .filter(|path| *path == filename || fs::read(path).is_ok_and(|data| sid_file::is_sid_file(&data)))
+            .collect::<Vec<_>>();
+
+        if files.len() <= 1 {
+            return Ok(());
+        }
+
+        let current_index = files.iter().position(|path| *path == filename).unwrap_or(0);
+
+        for offset in 1..files.len() {
+            let index = if forward {
+                (current_index + offset) % files.len()
+            } else {
+                (current_index + files.len() - offset) % files.len()
+            };
+
+            match self.player.lock().load_file(&files[index].to_string_lossy()) {
+                Ok(()) => return Ok(()),
+                Err(error) => error_log::write(&error)
+            }
+        }
+
+        self.player.lock().load_file(&filename.to_string_lossy())
+    }
+
+    fn is_song_limit_reached(&self) -> bool {
+        self.max_songs.is_some_and(|max_songs| self.played_songs.len() >= max_songs as usize)
+    }
+
     fn get_player_output(&mut self) -> PlayerOutput {
         *self.player_output.lock()
     }
@@ -160,6 +529,28 @@ impl ConsolePlayer {
         self.paused = true;
     }
 
+    fn restart_tune(&mut self, clock: &mut Clock) {
+        self.disable_fast_forward(clock);
+        self.send_command(PlayerCommand::Restart);
+        self.paused = false;
+        self.song_end_time = self.known_song_end_time;
+
+        clock.pause(false);
+        clock.set_clock(0);
+
+        if self.step_mode {
+            self.pause_tune();
+            clock.pause(true);
+        }
+    }
+
+    fn step_frame(&mut self, clock: &mut Clock) {
+        self.disable_fast_forward(clock);
+        self.send_command(PlayerCommand::StepFrame);
+        self.paused = true;
+        clock.pause(true);
+    }
+
     fn enable_fast_forward(&mut self) {
         let ff_in_progress = self.fast_forward_in_progress.load(Ordering::SeqCst);
         if !ff_in_progress {
@@ -205,21 +596,74 @@ impl ConsolePlayer {
 
     fn stop_player(&mut self, player_thread: thread::JoinHandle<()>) {
         self.abort_type.store(ABORT_TO_QUIT, Ordering::SeqCst);
+
+        let stop_time = Instant::now();
+        while !player_thread.is_finished() {
+            if stop_time.elapsed().as_millis() >= STOP_PLAYER_TIMEOUT_IN_MILLIS {
+                self.force_exit();
+            }
+            thread::sleep(Duration::from_millis(LOOP_RATE_IN_MS));
+        }
+
         let _ = player_thread.join();
         self.abort_type.store(ABORTED, Ordering::SeqCst);
     }
 
+    // the hanging player thread holds the player lock, so the SID device can't be silenced anymore
+    fn force_exit(&self) -> ! {
+        eprintln!("\n\nWARNING: The SID device doesn't respond, exiting without silencing the SID device.");
+        process::exit(1);
+    }
+
     fn start_player(&mut self, clock: &mut Clock) -> thread::JoinHandle<()> {
         self.paused = false;
+        let song_number = self.player.lock().get_song_number();
+        let filename = self.player.lock().get_filename().unwrap_or_default();
+        self.played_songs.push((filename, song_number));
+
+        self.known_song_end_time = self.get_song_end_time(song_number);
+        self.song_end_time = self.known_song_end_time;
 
         self.disable_fast_forward(clock);
 
         self.abort_type.store(ABORT_NO, Ordering::SeqCst);
 
         let player_clone = Arc::clone(&self.player);
-        thread::spawn(move || {
+        let player_thread = thread::spawn(move || {
             player_clone.lock().play();
-        })
+        });
+
+        if self.step_mode {
+            self.pause_tune();
+            clock.pause(true);
+        }
+        player_thread
+    }
+
+    fn get_song_end_time(&self, song_number: i32) -> Option<u32> {
+        self.at_end?;
+
+        let player = self.player.lock();
+        if song_number != player.get_song_range().1 {
+            return None;
+        }
+
+        // a known length of 0 marks a silent sub tune, which ends immediately
+        player.get_known_song_length(song_number)
+            .filter(|song_length| *song_length >= 0)
+            .map(|song_length| song_length as u32)
+    }
+
+    // sub tunes with a known length of 0 are silent in the song length database and are skipped
+    fn get_next_non_silent_song(&self) -> i32 {
+        let player = self.player.lock();
+        let last_song_number = player.get_song_range().1;
+        let mut song_number = player.get_song_number() + 1;
+
+        while song_number <= last_song_number && player.get_known_song_length(song_number) == Some(0) {
+            song_number += 1;
+        }
+        song_number
     }
 
     fn is_aborted(&self) -> bool {
@@ -229,15 +673,30 @@ impl ConsolePlayer {
 
     fn refresh_info(&mut self, clock: &mut Clock) {
         clock.stop();
+        self.update_window_title();
+        #[cfg(unix)]
+        self.update_status_info();
+
+        if self.now_playing {
+            self.update_now_playing_info();
+            clock.start();
+            return;
+        }
+
         self.print_info();
-        let player = self.player.lock();
-        let song_number = player.get_song_number();
-        let song_length_in_milli = player.get_song_length(song_number);
-        let clock_display = Self::get_clock_display(song_length_in_milli);
-        print!("{clock_display}");
+        print!("{}", self.info.clock_display);
+        clock.set_song_length(self.get_song_length_in_seconds());
         clock.start();
     }
 
+    #[cfg(windows)]
+    fn is_console_focused() -> bool {
+        use windows::Win32::System::Console::GetConsoleWindow;
+        use windows::Win32::UI::WindowsAndMessaging::GetForegroundWindow;
+
+        unsafe { GetForegroundWindow() == GetConsoleWindow() }
+    }
+
     fn send_command(&mut self, command: PlayerCommand) {
         self.abort_type.store(ABORT_FOR_COMMAND, Ordering::SeqCst);
         let _ = self.player_cmd_sender.send(command);
@@ -249,45 +708,291 @@ impl ConsolePlayer {
     }
 
     fn setup_and_display_clock(&mut self) -> Clock {
-        let player = self.player.lock();
-        let song_number = player.get_song_number();
-        let song_length_in_milli = player.get_song_length(song_number);
-        let clock_display = ConsolePlayer::get_clock_display(song_length_in_milli);
-        print!("{clock_display}");
+        print!("{}", self.info.clock_display);
 
         let mut clock = Clock::new();
-        clock.set_clock_display_length(clock_display.len() - 1);
+        clock.set_clock_display_length(self.info.clock_display.len() - 1);
+        clock.set_song_length(self.get_song_length_in_seconds());
+        clock.set_buffer_meter(self.buffer_meter);
         clock
     }
 
-    fn get_clock_display(song_length_in_milli: i32) -> String {
-        if song_length_in_milli > 0 {
-            format!("(00:00 - {})", ConsolePlayer::convert_song_length(song_length_in_milli))
-        } else {
-            "(00:00)".to_string()
+    fn get_song_length_in_seconds(&self) -> Option<u32> {
+        self.info.song_length.filter(|&song_length| song_length > 0).map(|song_length| ((song_length + 500) / 1000) as u32)
+    }
+
+    // the estimated length is only used for display when the song length database has no entry
+    fn get_displayed_song_length(player: &Player, song_number: i32) -> (Option<i32>, bool) {
+        match player.get_known_song_length(song_number) {
+            Some(song_length) => (Some(song_length), false),
+            None => {
+                let estimated_song_length = player.get_estimated_song_length(song_number);
+                (estimated_song_length, estimated_song_length.is_some())
+            }
+        }
+    }
+
+    fn get_clock_display(song_length_in_milli: Option<i32>, estimated: bool) -> String {
+        let estimate_marker = if estimated { "~" } else { "" };
+        match song_length_in_milli {
+            Some(0) => "(00:00 - silent)".to_string(),
+            Some(song_length_in_milli) if song_length_in_milli > 0 => format!("(00:00 - {estimate_marker}{})", ConsolePlayer::convert_song_length(song_length_in_milli)),
+            _ => "(00:00)".to_string()
+        }
+    }
+
+    fn update_window_title(&mut self) {
+        if !self.window_title {
+            return;
+        }
+
+        let player = self.player.lock();
+        let title = format!("{} \u{2014} {} (song {}/{})", player.get_title().trim_end(), player.get_author().trim_end(), song_number::to_user_song_number(player.get_song_number()), player.get_number_of_songs());
+        drop(player);
+
+        term::set_window_title(&title);
+    }
+
+    fn update_now_playing_info(&mut self) {
+        let player = self.player.lock();
+        let song_number = player.get_song_number();
+        let song_length = match Self::get_displayed_song_length(&player, song_number) {
+            (Some(0), _) => " silent".to_string(),
+            (Some(song_length_in_milli), estimated) if song_length_in_milli > 0 => format!("/{}{}", if estimated { "~" } else { "" }, ConsolePlayer::convert_song_length(song_length_in_milli)),
+            _ => "".to_string()
+        };
+
+        self.now_playing_info = (
+            format!("{} \u{2014} {}", player.get_title().trim_end(), player.get_author().trim_end()),
+            format!("{song_length}] (song {}/{})", song_number::to_user_song_number(song_number), player.get_number_of_songs())
+        );
+        self.now_playing_seconds = None;
+    }
+
+    #[cfg(unix)]
+    fn update_status_info(&mut self) {
+        if self.status_socket.is_none() {
+            return;
+        }
+
+        let player = self.player.lock();
+        let song_number = player.get_song_number();
+
+        self.status_info = format!("\"title\":\"{}\",\"author\":\"{}\",\"song\":{},\"songs\":{},\"length\":{}",
+            json::escape_json(player.get_title().trim_end()),
+            json::escape_json(player.get_author().trim_end()),
+            song_number::to_user_song_number(song_number),
+            player.get_number_of_songs(),
+            player.get_known_song_length(song_number).unwrap_or(0));
+        self.status_update = None;
+    }
+
+    #[cfg(unix)]
+    fn send_status(&mut self, clock: &Clock) {
+        if let Some(status_socket) = self.status_socket.as_ref() {
+            let seconds = clock.get_clock() / 1000;
+
+            if self.status_update != Some((seconds, self.paused)) {
+                self.status_update = Some((seconds, self.paused));
+                status_socket.send(&format!("{{{},\"time\":{},\"paused\":{}}}", self.status_info, clock.get_clock(), self.paused));
+            }
+        }
+    }
+
+    fn print_now_playing(&mut self, clock: &Clock) {
+        let seconds = clock.get_clock() / 1000;
+
+        if self.now_playing_seconds != Some(seconds) {
+            self.now_playing_seconds = Some(seconds);
+
+            let time = Clock::convert_seconds_to_time_string(seconds as u32, false);
+            println!("{} [{time}{}", self.now_playing_info.0, self.now_playing_info.1);
+            let _ = stdout().flush();
         }
     }
 
     pub fn print_info(&mut self) {
-        self.print_filename();
-        self.print_sid_model();
-        self.print_c64_model();
-        self.print_sid_description();
+        self.update_info();
+        self.print_cached_info();
+    }
+
+    fn update_info(&mut self) {
+        let mut info = String::new();
+        self.write_filename(&mut info);
+        self.write_sid_model(&mut info);
+        self.write_c64_model(&mut info);
+        self.write_digi_type(&mut info);
+        self.write_sid_description(&mut info);
+
+        let mut device_info = String::new();
+        self.write_device_info(&mut device_info);
+
+        let player = self.player.lock();
+        let stil_entry = player.get_stil_entry();
+        let (song_length, estimated) = Self::get_displayed_song_length(&player, player.get_song_number());
+        let clock_display = Self::get_clock_display(song_length, estimated);
+        drop(player);
+
+        self.info = InfoBlock { info, stil_entry, device_info, clock_display, song_length };
+    }
+
+    fn print_cached_info(&self) {
+        print!("{}", self.info.info);
         self.print_stil_info();
-        self.print_device_info();
+        print!("{}", self.info.device_info);
 
         print!("\nPress escape key to exit... ");
     }
 
-    fn print_filename(&mut self) {
+    fn toggle_stil_info(&mut self, clock: &mut Clock) {
+        if !self.player_has_stil {
+            if !self.now_playing {
+                clock.stop();
+                print!("\nSTIL info is not available, specify the HVSC location with option -l.\n");
+                self.reprint_clock(clock);
+            }
+            return;
+        }
+
+        self.display_stil = !self.display_stil;
+
+        if !self.now_playing {
+            clock.stop();
+            self.print_cached_info();
+            self.reprint_clock(clock);
+        }
+    }
+
+    fn toggle_volume_fix(&mut self, clock: &mut Clock) {
+        self.send_command(PlayerCommand::ToggleVolumeFix);
+        self.volume_fix = !self.volume_fix;
+
+        if !self.now_playing {
+            clock.stop();
+            print!("\nVolume fix: {}\n", if self.volume_fix { "on" } else { "off" });
+            self.reprint_clock(clock);
+        }
+    }
+
+    fn change_volume(&mut self, step: i32, clock: &mut Clock) {
+        self.volume = (self.volume as i32 + step).clamp(0, MAX_VOLUME as i32) as u8;
+        self.send_command(PlayerCommand::SetVolume(self.volume));
+
+        let message = format!("Vol: {}/{MAX_VOLUME}", self.volume);
+        if self.now_playing {
+            eprintln!("{message}");
+        } else {
+            clock.show_message(&message);
+        }
+    }
+
+    pub fn get_volume(&self) -> u8 {
+        self.volume
+    }
+
+    fn reload_hvsc_databases(&mut self, clock: &mut Clock) {
+        let Some(hvsc_root) = self.hvsc_root.clone() else {
+            return;
+        };
+
+        let message = match HvscDatabases::load(&hvsc_root, self.display_stil) {
+            Ok(hvsc_databases) => {
+                self.send_command(PlayerCommand::ReplaceHvscDatabases(Box::new(hvsc_databases)));
+                "Song lengths and STIL reloaded.".to_string()
+            },
+            Err(error) => format!("Reloading song lengths and STIL failed: {error}")
+        };
+
+        if self.now_playing {
+            eprintln!("{message}");
+        } else {
+            clock.stop();
+            print!("\n{message}\n");
+            self.reprint_clock(clock);
+        }
+    }
+
+    fn print_digi_warning(&mut self, limited_digi_support: bool, clock: &mut Clock) {
+        self.digi_warning_shown = true;
+
+        if limited_digi_support {
+            self.print_warning("Warning: digi playback detected, samples may sound degraded on the current device.", clock);
+        }
+        if self.balance_applied {
+            self.print_warning("Warning: digi playback detected, the balance scales the volume register and may distort the samples.", clock);
+        }
+    }
+
+    fn query_player_state(&self) -> Option<PlayerState> {
+        let (state_sender, state_receiver) = sync_channel(1);
+        self.player_query_sender.try_send(PlayerQuery::CurrentState(state_sender)).ok()?;
+        state_receiver.recv_timeout(Duration::from_millis(PLAYER_QUERY_TIMEOUT_IN_MILLIS)).ok()
+    }
+
+    fn print_player_state(&mut self, clock: &mut Clock) {
+        let Some(state) = self.query_player_state() else {
+            self.print_warning("Player state is not available.", clock);
+            return;
+        };
+
+        let mut message = format!("Time {}, song {}{}, CPU load {}%",
+            Clock::convert_seconds_to_time_string(state.time / 1000, false),
+            song_number::to_user_song_number(state.song_number),
+            if state.paused { " (paused)" } else { "" },
+            state.cpu_load);
+
+        for (sid_nr, sid_registers) in state.sid_registers.chunks(0x20).enumerate() {
+            let registers = sid_registers[..0x19].iter().map(|data| format!("{data:02X}")).collect::<Vec<String>>().join(" ");
+            let _ = write!(message, "\nSID {}: {registers}", sid_nr + 1);
+        }
+        self.print_warning(&message, clock);
+    }
+
+    fn print_warning(&mut self, warning: &str, clock: &mut Clock) {
+        if self.now_playing {
+            eprintln!("{warning}");
+        } else {
+            clock.stop();
+            print!("\n{warning}\n");
+            self.reprint_clock(clock);
+        }
+    }
+
+    fn print_detected_loop(&mut self, detected_loop: &DetectedLoop, clock: &mut Clock) {
+        let message = Self::get_detected_loop_message(detected_loop);
+
+        if self.now_playing {
+            eprintln!("{message}");
+        } else {
+            clock.stop();
+            print!("\n{message}\n");
+            self.reprint_clock(clock);
+        }
+    }
+
+    fn get_detected_loop_message(detected_loop: &DetectedLoop) -> String {
+        format!("Loop detected: starts at {}, duration {}",
+            ConsolePlayer::convert_song_length(detected_loop.start_in_millis as i32),
+            ConsolePlayer::convert_song_length(detected_loop.duration_in_millis as i32))
+    }
+
+    fn reprint_clock(&mut self, clock: &mut Clock) {
+        let time = clock.get_clock();
+        print!("{}", self.info.clock_display);
+        clock.start();
+        clock.set_clock(time);
+        clock.pause(self.paused);
+    }
+
+    fn write_filename(&mut self, info: &mut String) {
         let filename = self.player.lock().get_filename();
         if let Some(filename) = filename {
             let path = Path::new(&filename);
-            println!("\nFile            : {}", path.file_name().unwrap().to_str().unwrap());
+            let _ = writeln!(info, "\n{} {}", term::dim("File            :"), path.file_name().unwrap().to_str().unwrap());
         }
     }
 
-    fn print_sid_model(&mut self) {
+    fn write_sid_model(&mut self, info: &mut String) {
         let sid_model = self.player.lock().get_sid_model();
         let sid_model_display = match sid_model {
             1 => "MOS 6581",
@@ -295,10 +1000,10 @@ impl ConsolePlayer {
             3 => "MOS 6581/8580",
             _ => "Unknown"
         };
-        println!("SID Model       : {sid_model_display}");
+        let _ = writeln!(info, "{} {sid_model_display}", term::dim("SID Model       :"));
     }
 
-    fn print_c64_model(&mut self) {
+    fn write_c64_model(&mut self, info: &mut String) {
         let c64_model = self.player.lock().get_c64_version();
         let c64_model_display = match c64_model {
             1 => "PAL",
@@ -306,37 +1011,44 @@ impl ConsolePlayer {
             3 => "PAL/NTSC",
             _ => "Unknown"
         };
-        println!("Clock Frequency : {c64_model_display}");
+        let _ = writeln!(info, "{} {c64_model_display}", term::dim("Clock Frequency :"));
     }
 
-    fn print_sid_description(&mut self) {
+    fn write_digi_type(&mut self, info: &mut String) {
+        if let Some(digi_type) = self.player.lock().scan_digi_type() {
+            let _ = writeln!(info, "{} detected ({digi_type})", term::dim("Digi            :"));
+        }
+    }
+
+    fn write_sid_description(&mut self, info: &mut String) {
         let player = self.player.lock();
         let title = player.get_title();
         let author = player.get_author();
         let released = player.get_released();
 
-        if (title.len() > 32) && author.is_empty() && released.is_empty() {
-            println!("\n       Sidplayer 64 info");
-            println!("================================");
-            println!("{}", title.trim_end());
+        if let Some(mus_text) = player.get_mus_text() {
+            let _ = writeln!(info, "\n       Sidplayer 64 info");
+            let _ = writeln!(info, "================================");
+            for line in petscii::decode_lines(&mus_text, MUS_TEXT_LINE_LENGTH) {
+                let _ = writeln!(info, "{line}");
+            }
         } else {
-            println!("\nTitle           : {title}");
-            println!("Author          : {author}");
-            println!("Released        : {released}");
+            let _ = writeln!(info, "\n{} {}", term::dim("Title           :"), term::bold(&title));
+            let _ = writeln!(info, "{} {author}", term::dim("Author          :"));
+            let _ = writeln!(info, "{} {released}", term::dim("Released        :"));
         }
     }
 
-    fn print_stil_info(&mut self) {
+    fn print_stil_info(&self) {
         if self.display_stil {
-            let stil_entry = self.player.lock().get_stil_entry();
-            if stil_entry.is_some() {
+            if let Some(stil_entry) = &self.info.stil_entry {
                 println!("\nSTIL Info");
-                println!("---------\n{}", stil_entry.unwrap());
+                println!("---------\n{stil_entry}");
             }
         }
     }
 
-    fn print_device_info(&mut self) {
+    fn write_device_info(&mut self, info: &mut String) {
         let mut player= self.player.lock();
         let device_numbers = player.get_device_numbers();
         let song_number = player.get_song_number();
@@ -344,15 +1056,44 @@ impl ConsolePlayer {
         let number_of_sids = player.get_number_of_sids();
 
         if number_of_sids > 1 {
-            println!("\nPlaying song {} of {} on devices:", song_number + 1, number_of_songs);
+            let _ = writeln!(info, "\nPlaying song {} of {} on devices:", song_number::to_user_song_number(song_number), number_of_songs);
             for i in 0..number_of_sids {
                 let device_info = player.get_device_info(device_numbers[i as usize]);
-                println!("SID {} -> {:>2}: {}", i + 1, device_numbers[i as usize] + 1, device_info);
+                let _ = writeln!(info, "SID {} -> {:>2}: {}", i + 1, device_numbers[i as usize] + 1, device_info);
+            }
+
+            if player.is_stereo_swapped() {
+                let _ = writeln!(info, "Stereo channels are swapped.");
             }
 
         } else {
             let device_info = player.get_device_info(device_numbers[0]);
-            println!("\nPlaying song {} of {} on device {}: {}", song_number + 1, number_of_songs, device_numbers[0] + 1, device_info);
+            let _ = writeln!(info, "\nPlaying song {} of {} on device {}: {}", song_number::to_user_song_number(song_number), number_of_songs, device_numbers[0] + 1, device_info);
+        }
+
+        if self.warn_chip {
+            for i in 0..number_of_sids {
+                if player.has_sid_model_mismatch(i) {
+                    let _ = writeln!(info, "{}", term::bold(&format!("WARNING: SID {} requires a different SID model than device {} provides.", i + 1, device_numbers[i as usize] + 1)));
+                }
+            }
+        }
+
+        for (source_sid_nr, mirror_sid_nr, sid_address) in player.get_mirrored_sids() {
+            let _ = writeln!(info, "Note: SID {} shares address ${sid_address:04X} with SID {}, writes are sent to both SID chips.", mirror_sid_nr + 1, source_sid_nr + 1);
+        }
+
+        let unsupported_features = player.get_unsupported_features();
+        if !unsupported_features.is_empty() {
+            let _ = writeln!(info, "Note: not supported by the device: {}.", unsupported_features.join(", "));
+        }
+
+        if self.step_mode && !player.has_remote_sidplayer() {
+            let _ = writeln!(info, "Step mode: press space to play the next frame and p to resume playback.");
+        }
+
+        if player.has_remote_sidplayer() {
+            let _ = writeln!(info, "Note: the remote device restarts the song when resuming from pause.");
         }
     }
 }
@@ -2,10 +2,16 @@
 // Licensed under the GNU GPL v3 license. See the LICENSE file for the terms and conditions.
 
 use std::io::stdout;
+use crate::utils::term;
 use crossterm::cursor::{Hide, MoveLeft, MoveRight, SavePosition, RestorePosition, Show};
 use crossterm::execute;
 use std::sync::atomic::{AtomicBool, AtomicUsize, Ordering};
 use std::sync::Arc;
+use std::time::{Duration, Instant};
+
+const BUFFER_METER_WIDTH: usize = 10;
+const BUFFER_METER_LENGTH: u16 = BUFFER_METER_WIDTH as u16 + 3;
+const MESSAGE_DURATION: Duration = Duration::from_secs(2);
 
 pub struct Clock {
     counter: Arc<AtomicUsize>,
@@ -13,7 +19,14 @@ pub struct Clock {
     previous_count: usize,
     guard: Option<timer::Guard>,
     clock_length: u16,
-    paused: Arc<AtomicBool>
+    song_length_in_seconds: Option<u32>,
+    buffer_meter: bool,
+    buffer_fill: Option<f32>,
+    previous_buffer_meter: Option<String>,
+    message: Option<(String, Instant)>,
+    message_length: u16,
+    paused: Arc<AtomicBool>,
+    visible: bool
 }
 
 impl Clock {
@@ -24,14 +37,46 @@ impl Clock {
             previous_count: 0,
             guard: None,
             clock_length: 0,
-            paused: Arc::new(AtomicBool::new(false))
+            song_length_in_seconds: None,
+            buffer_meter: false,
+            buffer_fill: None,
+            previous_buffer_meter: None,
+            message: None,
+            message_length: 0,
+            paused: Arc::new(AtomicBool::new(false)),
+            visible: true
         }
     }
 
+    pub fn set_visible(&mut self, visible: bool) {
+        self.visible = visible;
+    }
+
     pub fn set_clock_display_length(&mut self, clock_length: usize) {
         self.clock_length = clock_length as u16;
     }
 
+    pub fn set_song_length(&mut self, song_length_in_seconds: Option<u32>) {
+        self.song_length_in_seconds = song_length_in_seconds;
+    }
+
+    pub fn set_buffer_meter(&mut self, buffer_meter: bool) {
+        self.buffer_meter = buffer_meter;
+    }
+
+    pub fn set_buffer_fill(&mut self, buffer_fill: Option<f32>) {
+        self.buffer_fill = buffer_fill;
+    }
+
+    // shows the message behind the clock for a short time
+    pub fn show_message(&mut self, message: &str) {
+        if self.visible {
+            self.clear_message();
+            self.message = Some((message.to_string(), Instant::now()));
+            self.print_message(&format!(" {message}"));
+        }
+    }
+
     pub fn start(&mut self) {
         self.pause(false);
         self.counter.store(0, Ordering::Relaxed);
@@ -47,32 +92,91 @@ impl Clock {
             })
         };
         self.guard = Some(guard);
+        self.previous_buffer_meter = None;
 
-        execute!(stdout(), Hide, MoveLeft(self.clock_length), SavePosition).unwrap();
+        if self.visible {
+            execute!(stdout(), Hide, MoveLeft(self.clock_length), SavePosition).unwrap();
+        }
     }
 
     pub fn set_clock(&mut self, millis: usize) {
         self.counter.store(millis, Ordering::Relaxed);
     }
 
+    pub fn get_clock(&self) -> usize {
+        self.counter.load(Ordering::Relaxed)
+    }
+
     pub fn pause(&mut self, pause: bool) {
         self.paused.store(pause, Ordering::Relaxed);
     }
 
     pub fn stop(&mut self) {
         self.guard = None;
-        execute!(stdout(), MoveRight(self.clock_length), Show).unwrap();
+
+        if self.visible {
+            self.clear_message();
+
+            let buffer_meter_length = if self.buffer_meter { BUFFER_METER_LENGTH } else { 0 };
+            execute!(stdout(), MoveRight(self.clock_length + buffer_meter_length), Show).unwrap();
+        }
     }
 
     pub fn refresh_clock(&mut self) {
         let millis = self.counter.load(Ordering::Relaxed);
 
-        if self.previous_count != millis {
+        if self.visible && self.buffer_meter {
+            self.refresh_buffer_meter();
+        }
+
+        if self.message.as_ref().is_some_and(|(_, shown)| shown.elapsed() >= MESSAGE_DURATION) {
+            self.clear_message();
+        }
+
+        if self.visible && self.previous_count != millis {
             self.previous_count = millis;
 
-            let time = Clock::convert_seconds_to_time_string((millis / 1000) as u32, false);
-            print!("{time}");
+            let seconds = (millis / 1000) as u32;
+            let time = Clock::convert_seconds_to_time_string(seconds, false);
+
+            // the time is shown in a different color when playing past the end of the song
+            if self.song_length_in_seconds.is_some_and(|song_length| seconds > song_length) {
+                print!("{}", term::yellow(&time));
+            } else {
+                print!("{}", term::cyan(&time));
+            }
+            execute!(stdout(), RestorePosition).unwrap();
+        }
+    }
+
+    fn clear_message(&mut self) {
+        if self.message.take().is_some() {
+            self.print_message(&" ".repeat(self.message_length as usize));
+        }
+    }
+
+    fn print_message(&mut self, text: &str) {
+        let buffer_meter_length = if self.buffer_meter { BUFFER_METER_LENGTH } else { 0 };
+        execute!(stdout(), MoveRight(self.clock_length + buffer_meter_length)).unwrap();
+        print!("{text}");
+        execute!(stdout(), RestorePosition).unwrap();
+        self.message_length = text.len() as u16;
+    }
+
+    fn refresh_buffer_meter(&mut self) {
+        let buffer_meter = match self.buffer_fill {
+            Some(buffer_fill) => {
+                let filled = ((buffer_fill * BUFFER_METER_WIDTH as f32).round() as usize).min(BUFFER_METER_WIDTH);
+                format!(" [{}{}]", "#".repeat(filled), " ".repeat(BUFFER_METER_WIDTH - filled))
+            },
+            None => " ".repeat(BUFFER_METER_LENGTH as usize)
+        };
+
+        if self.previous_buffer_meter.as_ref() != Some(&buffer_meter) {
+            execute!(stdout(), MoveRight(self.clock_length)).unwrap();
+            print!("{buffer_meter}");
             execute!(stdout(), RestorePosition).unwrap();
+            self.previous_buffer_meter = Some(buffer_meter);
         }
     }
 
@@ -6,11 +6,23 @@ mod console_player;
 mod player;
 mod utils;
 
-use std::env;
+use std::{env, fs, thread};
+use std::io::{stdout, Write};
+use std::path::Path;
+use std::sync::atomic::Ordering;
+use std::time::Duration;
 use std::process::exit;
 use self::config::Config;
 use self::console_player::ConsolePlayer;
-use self::player::Player;
+use self::player::{Player, ABORT_TO_QUIT, MAX_VOLUME};
+use self::utils::{config_file, download, error_log, file, hvsc, json, keyboard, sid_file, term};
+use self::utils::profile::PhaseTimer;
+use self::utils::song_number::{self, DEFAULT_SONG};
+use self::utils::time_position::TimePosition;
+#[cfg(unix)]
+use self::utils::status_socket::StatusSocket;
+
+const SCAN_PROGRESS_INTERVAL: usize = 100;
 
 fn main() {
     if env::args().count() <= 1 {
@@ -21,6 +33,7 @@ fn main() {
     match run() {
         Ok(_) => {}
         Err(message) => {
+            error_log::write(&message);
             eprintln!("ERROR: {message}");
             exit(1);
         }
@@ -28,23 +41,179 @@ fn main() {
 }
 
 fn run() -> Result<(), String> {
-    let mut player = Player::new();
     let config = Config::read()?;
 
+    if let Some(error_log_filename) = &config.error_log_filename {
+        error_log::open(error_log_filename)?;
+    }
+
+    if config.print_config {
+        print!("{config}");
+    }
+
+    let timer = PhaseTimer::start(config.profile);
+    let mut player = Player::new();
+    timer.stop("library load");
+    player.set_profile(config.profile);
+
+    if config.timer_boost {
+        player.enable_timer_boost();
+    }
+
+    term::set_color_mode(config.color_mode);
+
     if config.adjust_clock {
         player.set_adjust_clock(true);
     }
 
+    if let Some(c64_version) = config.c64_version {
+        player.set_c64_version(c64_version);
+    }
+
+    if let Some(sid_clock) = config.sid_clock {
+        player.set_sid_clock(sid_clock);
+    }
+
+    player.set_fast_switch(config.fast_switch);
+    player.set_estimate_length(config.estimate_length);
+    player.set_balance(config.balance);
+
+    if let Some(volume) = config.volume {
+        player.set_volume(volume);
+    }
+    player.set_swap_stereo(config.swap_stereo);
+    player.set_trace_protocol(config.trace_protocol);
+    player.set_mono_downmix(config.mono_downmix);
+
+    if let Some(only_registers) = &config.only_registers {
+        player.set_register_whitelist(only_registers);
+    }
+    player.set_network_cycle_thresholds(config.net_flush_cycles, config.net_throttle_cycles)?;
+
+    if let Some(idle_timeout) = config.idle_timeout {
+        player.set_idle_timeout(idle_timeout);
+    }
+
+    if let Some(hs_busy_timeout) = config.hs_busy_timeout {
+        player.set_hardsid_busy_timeout(hs_busy_timeout);
+    }
+
+    if let Some(pause_sleep) = config.pause_sleep {
+        player.set_pause_sleep(pause_sleep as u64);
+    }
+
+    player.set_seek_accurate(config.seek_accurate);
+    player.set_reset_on_exit(config.reset_on_exit);
+    player.set_buffer_meter(config.buffer_meter);
+    player.set_device_types(config.enabled_device_types, &config.disabled_device_types)?;
+
+    if let Some(sampling_method) = config.sampling_method {
+        player.set_sampling_method(sampling_method);
+    }
+
+    if let Some(fade_in) = config.fade_in {
+        player.set_fade_in(fade_in);
+    }
+
+    if let Some(preferred_device_type) = config.preferred_device_type {
+        player.set_preferred_device_type(preferred_device_type);
+    }
+
+    if let Some(skip_empty) = config.skip_empty {
+        player.set_skip_empty(skip_empty);
+    }
+
+    if let Some(connect_timeout) = config.connect_timeout {
+        player.set_connection_timeout(connect_timeout as u64);
+    }
+
+    if let Some(ftdi_latency) = config.ftdi_latency {
+        player.set_ftdi_latency(ftdi_latency as u64);
+    }
+
+    if let Some(reglog_filename) = config.reglog_filename {
+        player.set_reglog_filename(reglog_filename);
+    }
+
     if let Some(host_name) = config.host_name_sid_device {
         player.set_sid_device_host_name(host_name);
     }
 
+    if let Some(sid_variant) = config.sid_variant {
+        player.set_sid_variant(sid_variant);
+    }
+
+    if let Some(host_name) = config.tee_host_name {
+        player.set_tee_host_name(host_name);
+    }
+
     if let Some(host_name) = config.host_name_ultimate_device {
         player.set_ultimate_device_host_name(host_name);
     }
 
+    if let Some(scan_directory) = config.scan_directory {
+        return scan_directory_tree(&mut player, &scan_directory, config.hvsc_location);
+    }
+
+    let downloaded_file = if download::is_url(&config.filename) {
+        Some(download::download_file(&config.filename)?)
+    } else {
+        None
+    };
+    let filename = downloaded_file.as_ref().map_or(config.filename.clone(), |file| file.get_filename());
+
+    if config.hexdump {
+        let file_data = fs::read(&filename).map_err(|error| format!("Could not read file {filename} -> {error}"))?;
+        if !sid_file::is_sid_file(&file_data) {
+            return Err(format!("File {filename} is not a PSID or RSID file."));
+        }
+        print!("{}", sid_file::hexdump_header(&file_data));
+        return Ok(());
+    }
+
+    if config.hvsc_path {
+        player.setup_sldb_and_stil(config.hvsc_location, false)?;
+        player.load_file_data(&filename)?;
+
+        match player.get_hvsc_filename() {
+            Some(hvsc_filename) => println!("{hvsc_filename}"),
+            None => {
+                drop(downloaded_file);
+                exit(1);
+            }
+        }
+        return Ok(());
+    }
+
+    if config.frames.is_some() || config.fingerprint_filename.is_some() {
+        player.setup_sldb_and_stil(config.hvsc_location, false)?;
+        player.load_file_data(&filename)?;
+        select_song_to_analyze(&mut player, config.song_title, config.song_number)?;
+
+        if let Some(fingerprint_filename) = config.fingerprint_filename {
+            player.create_fingerprint(&fingerprint_filename)?;
+            println!("Fingerprint of song {} written to {fingerprint_filename}.", song_number::to_user_song_number(player.get_song_number()));
+        }
+
+        if let Some(frames) = config.frames {
+            if config.stats {
+                player.enable_register_stats();
+            }
+
+            let statistics = player.run_frames(frames);
+            println!("Song {}: ran {} frames ({} cycles) with {} SID writes.", song_number::to_user_song_number(player.get_song_number()), statistics.frames, statistics.cycles, statistics.sid_writes);
+
+            if let Some(report) = player.get_register_stats_report() {
+                print!("\n{report}");
+            }
+        }
+        return Ok(());
+    }
+
     player.set_device_numbers(config.device_numbers);
-    player.init_devices()?;
+    let timer = PhaseTimer::start(config.profile);
+    player.init_devices().map_err(|error| format!("{error}\nUse option -p to list the available devices or specify the network device with -hs{{host_name}} or -hu{{ip_address}}."))?;
+    timer.stop("device connect");
 
     if config.display_devices {
         let device_names = player.get_device_names();
@@ -52,43 +221,270 @@ fn run() -> Result<(), String> {
         return Ok(());
     }
 
+    if let Some(play_reglog_filename) = config.play_reglog_filename {
+        return play_register_log(player, play_reglog_filename);
+    }
+
+    if config.self_test {
+        return self_test(&mut player);
+    }
+
     player.setup_sldb_and_stil(config.hvsc_location, config.display_stil)?;
-    player.load_file(&config.filename)?;
-    if config.song_number != -1 {
+    player.load_file(&filename)?;
+    if let Some(song_title) = config.song_title {
+        let song_number = player.find_song_by_title(&song_title)
+            .ok_or(format!("No song found with a STIL title or name containing '{song_title}'."))?;
+        player.set_song_to_play(song_number)?;
+    } else if config.song_number != DEFAULT_SONG {
         player.set_song_to_play(config.song_number)?;
     }
 
+    if let Some((first_song, last_song)) = config.song_range {
+        player.set_song_range(first_song, last_song)?;
+
+        if !(first_song..=last_song).contains(&player.get_song_number()) {
+            player.set_song_to_play(first_song)?;
+        }
+    }
+
     let version = player.get_library_version();
     if version < 0x210 {
         return Err("acid64pro.dll version 2.1.0 or higher required.".to_string());
     }
 
-    print_library_version(version);
+    let clip = resolve_clip(&player, config.clip)?;
+
+    if let Some((start_time, end_time)) = clip {
+        if start_time > 0 && player.has_remote_sidplayer() {
+            return Err("Clip start time is not supported for remote devices.".to_string());
+        }
+
+        if let Some(song_length) = player.get_known_song_length(player.get_song_number()) {
+            if end_time as i32 > song_length {
+                return Err(format!("Clip end time exceeds the song length of {} seconds.", (song_length + 500) / 1000));
+            }
+        }
+    }
+
+    if config.stats {
+        player.enable_register_stats();
+    }
+
+    if config.detect_loop {
+        player.enable_loop_detection(config.stop_at_loop);
+    }
+
+    if !config.now_playing {
+        print_library_version(version);
+    }
 
     let mut console_player = ConsolePlayer::new(player, config.display_stil);
+    console_player.set_now_playing(config.now_playing);
+    console_player.set_warn_chip(config.warn_chip);
+    console_player.set_step_mode(config.step_mode);
+    console_player.set_buffer_meter(config.buffer_meter);
+    console_player.set_window_title(config.window_title);
+    #[cfg(windows)]
+    console_player.set_mute_on_unfocus(config.mute_on_unfocus);
+    #[cfg(unix)]
+    if let Some(status_socket) = config.status_socket {
+        console_player.set_status_socket(StatusSocket::new(&status_socket)?);
+    }
+
+    if let Some(at_end) = config.at_end {
+        console_player.set_at_end(at_end);
+    }
+
+    if let Some(max_songs) = config.max_songs {
+        console_player.set_max_songs(max_songs);
+    }
+
+    if let Some((start_time, end_time)) = clip {
+        console_player.set_clip(start_time, end_time);
+    }
     console_player.play()?;
+
+    if let Some(report) = console_player.get_register_stats_report() {
+        print!("\n{report}");
+    }
+
+    let volume = console_player.get_volume();
+    if config.volume.unwrap_or(MAX_VOLUME) != volume {
+        if let Err(error) = config_file::update_value("volume", &volume.to_string()) {
+            eprintln!("WARNING: The volume could not be saved -> {error}");
+        }
+    }
+    Ok(())
+}
+
+fn self_test(player: &mut Player) -> Result<(), String> {
+    println!("Playing a frequency sweep on voice 1 of the first SID.");
+    player.self_test().map_err(|error| format!("Self test failed -> {error}"))?;
+
+    let device_number = player.get_device_numbers()[0];
+    println!("Self test passed on device {}: {}", device_number + 1, player.get_device_info(device_number));
     Ok(())
 }
 
+fn play_register_log(mut player: Player, filename: String) -> Result<(), String> {
+    let abort_type = player.get_aborted_ref();
+    let exiting = player.get_exiting_ref();
+    println!("Playing register log {filename}, press ESC to stop.");
+
+    let player_thread = thread::spawn(move || player.play_register_log(&filename));
+
+    while !player_thread.is_finished() {
+        if keyboard::get_char_from_input() == Some(keyboard::ESC_KEY) {
+            exiting.store(true, Ordering::SeqCst);
+            abort_type.store(ABORT_TO_QUIT, Ordering::SeqCst);
+        }
+        thread::sleep(Duration::from_millis(50));
+    }
+
+    player_thread.join().map_err(|_| "Register log playback stopped unexpectedly.".to_string())?
+}
+
 fn print_usage() {
     println!("ACID64 Console v1.09 - Copyright (c) 2003-2023 Wilfred Bos");
-    println!("\nUsage: acid64c <options> <file_name>");
+    println!("\nUsage: acid64c <options> <file_name or url>");
     println!("\n<Options>");
     println!("  -c: adjust clock for devices that don't support PAL/NTSC clock");
+    println!("  -C{{clock}}: force the SID clock: pal, ntsc, ntsc-old or drean");
     println!("  -d{{device_number,n}}: set device numbers (1..n) for each SID chip, default is 1");
     println!("  -hs{{host_name}}: host name or IP of network sid device, default is localhost");
     println!("  -hu{{ip_address}}: IP of Ultimate device");
     println!("  -i: display STIL info if present");
     println!("  -l{{hvsc_location}}: specify the HVSC location for song length and STIL info");
     println!("  -p: print available devices");
-    println!("  -s{{song_number}}: set song number (1..n), default is start song in SID file");
+    println!("  -s{{song_number}}: set song number (1..n) or the first song with a STIL title or name containing the text, default is start song in SID file");
+    println!("  --at-end {{stop|loop|next-file|quit}}: action when the last sub tune reaches its song length, default is to keep playing");
+    println!("  --balance {{-100..100}}: balance the volume of the left (first) and right (second) SID for stereo tunes");
+    println!("  --buffer-meter: show the buffer fill level of the network SID device or SIDBlaster next to the clock, for diagnosing stutter");
+    println!("  --c64-version {{0..3}}: override C64 clock version (0 = unknown, 1 = PAL, 2 = NTSC, 3 = PAL/NTSC)");
+    println!("  --clip {{start-end}}: only play the part of the song between the start and end time, each time as mm:ss, a percentage of the song length (50%) or a time before the end (-0:10)");
+    println!("  --color {{auto|always|never}}: colored output, auto disables colors when not writing to a terminal or when NO_COLOR is set");
+    println!("  --connect-timeout {{ms}}: timeout for connecting to the network SID device, 0 = OS default (default: 1000)");
+    println!("  --detect-loop {{report|stop}}: detect a repeating SID register pattern to find the loop point of tunes without a song length, stop ends playback when detected");
+    println!("  --disable {{device_type,n}}: don't connect to the given device types: hardsid, sidblaster, network or ultimate");
+    println!("  --enable {{device_type,n}}: only connect to the given device types: hardsid, sidblaster, network or ultimate");
+    println!("  --error-log {{file_name}}: append timestamped errors, such as device disconnects and load failures, to the specified file");
+    println!("  --estimate-length: estimate the length of songs missing in the song length database by emulating them silently until they become silent or loop, takes up to 5 seconds per sub tune, the estimate is only displayed and marked with a ~");
+    println!("  --fade-in {{millis}}: fade in the volume at the start of a sub tune, only supported by network devices with interface version 4 or higher");
+    println!("  --fast-switch: only reset the active SID chips when switching sub tunes");
+    println!("  --fingerprint {{file_name.svg}}: emulate the song without output and write a heatmap of the SID register writes per second as an SVG image, then exit");
+    println!("  --frames {{n}}: run the emulation for n frames without output, print the number of SID writes and exit");
+    println!("  --ftdi-latency {{ms}}: FTDI latency timer for SIDBlaster USB devices (1..255), default is 2");
+    println!("  --hexdump: print the PSID/RSID header of the file as an annotated hex dump and exit");
+    println!("  --hs-busy-timeout {{ms}}: disconnect a HardSID USB device with an error when it stays busy for longer than the given time, default is to wait forever");
+    println!("  --hvsc-path: print the HVSC path of the file found via the song length database and exit, exits with code 1 when not found");
+    println!("  --idle-timeout {{seconds}}: exit when no SID registers are accessed for the given number of seconds");
+    println!("  --max-songs {{n}}: stop advancing automatically after n sub tunes have been played, selecting a sub tune or file with the keys still works");
+    println!("  --mono-downmix: send the writes of all SID chips to the first SID chip when the device can't play multi-SID tunes, instead of dropping them");
+    #[cfg(windows)]
+    println!("  --mute-on-unfocus: mute playback while the console window is not focused (Windows only)");
+    println!("  --net-flush-cycles {{cycles}}: send buffered SID writes to the network SID device after this many cycles (1000..100000), default is 9828");
+    println!("  --net-throttle-cycles {{cycles}}: wait briefly after sending more than this many cycles to the network SID device (2000..1000000), default is 20000");
+    println!("  --no-reset-on-exit: don't silence the SID chips when the program exits, the last register state is kept and a note can keep sounding");
+    println!("  --no-timer-boost: don't raise the Windows timer resolution to 1 ms, saves power at the cost of less accurate timing");
+    println!("  --no-warn-chip: don't warn when the SID model of a device doesn't match the SID model required by the tune");
+    println!("  --no-{{option}}: disable an option without a value that is enabled in the config file, e.g. --no-nowplaying");
+    println!("  --nowplaying: print a single now playing status line per second instead of the full display");
+    println!("  --only-regs {{reg,n}}: only send the writes to the given SID registers in hex, e.g. 15-18 for the filter of SID 1 or 00-06 for voice 1, other writes are replaced by delays except the volume of register 18 which is always sent, register $20 and up are the registers of SID 2 and 3");
+    println!("  --pause-sleep {{milliseconds}}: interval for checking commands while paused, default is 50");
+    println!("  --playreglog {{file_name}}: play a register log created with --reglog on the selected device without emulation");
+    println!("  --prefer {{device_type}}: list the devices of the given type first so they become the default: hardsid, sidblaster, network or ultimate");
+    println!("  --print-config: print the resolved settings of the command line and config file with the defaults applied before playing");
+    println!("  --profile: print the duration of the startup phases and the latency of the first SID write to stderr");
+    println!("  --quality {{fast|accurate}}: sampling method of the emulation on network SID devices, fast lowers the CPU load of the server, default is accurate");
+    println!("  --reglog {{file_name}}: log all SID register writes with their timing to the specified file");
+    println!("  --scan {{directory}}: print the metadata and song lengths of all SID files in the directory and its sub directories as JSON lines and exit");
+    println!("  --seek-accurate: seek by emulating the song silently up to the start of the clip instead of using the fast library seek");
+    println!("  --selftest: play a short frequency sweep on the selected device without a SID file to check if the device works, then exit");
+    println!("  --set-title: show the title, author and sub tune in the terminal window title, only when writing to a terminal");
+    println!("  --sid-variant {{name}}: select the network SID device configuration whose name contains the text, e.g. R4AR, falls back to a configuration with the SID model of the tune");
+    println!("  --skip-empty {{seconds}}: skip to the next sub tune when no SID output occurs within the given number of seconds after init");
+    println!("  --songs {{first-last}}: only play the sub tunes in the given range, e.g. 3-7, the sub tune keys and skipping empty sub tunes stay within the range");
+    println!("  --stats: print SID register write statistics on exit");
+    #[cfg(unix)]
+    println!("  --status-socket {{path}}: send the now playing state as JSON to the given Unix datagram socket every second (Unix only)");
+    println!("  --step: start paused and play one frame per press of the space key, for debugging SID drivers");
+    println!("  --swap-stereo: swap the left and right channel of stereo tunes on network SID devices");
+    println!("  --tee {{host_name}}: also send all SID writes to the network SID device at the given host, e.g. for recording");
+    println!("  --trace-protocol: print all commands sent to and responses received from the network SID device to stderr");
+    println!("  --volume {{0..15}}: master volume, can be changed with the < and > keys during playback and is stored in the config file when present, default is 15");
+}
+
+fn scan_directory_tree(player: &mut Player, directory: &str, hvsc_location: Option<String>) -> Result<(), String> {
+    let hvsc_location = hvsc_location.or_else(|| hvsc::get_hvsc_root(directory));
+    player.setup_sldb_and_stil(hvsc_location, false)?;
+
+    let files = file::get_sorted_files_in_directory_tree(Path::new(directory));
+    let mut stdout = stdout().lock();
+    let mut sid_files = 0;
+
+    for (index, filename) in files.iter().enumerate() {
+        if index % SCAN_PROGRESS_INTERVAL == 0 {
+            eprint!("\rScanning file {} of {}...", index + 1, files.len());
+        }
+
+        let filename = filename.to_string_lossy();
+        if player.load_file_data(&filename).is_err() {
+            continue;
+        }
+        sid_files += 1;
+
+        let song_lengths = (0..player.get_number_of_songs())
+            .map(|song_number| player.get_known_song_length(song_number).map_or("null".to_string(), |song_length| song_length.to_string()))
+            .collect::<Vec<String>>();
+
+        let release_year = player.get_release_year()
+            .map_or("null".to_string(), |release_year| format!("{{\"first\":{},\"last\":{}}}", release_year.first, release_year.last));
+
+        writeln!(stdout, "{{\"file\":\"{}\",\"md5\":\"{}\",\"title\":\"{}\",\"author\":\"{}\",\"released\":\"{}\",\"release_year\":{},\"songs\":{},\"song_lengths\":[{}]}}",
+            json::escape_json(&filename),
+            player.get_md5_hash(),
+            json::escape_json(player.get_title().trim_end()),
+            json::escape_json(player.get_author().trim_end()),
+            json::escape_json(player.get_released().trim_end()),
+            release_year,
+            player.get_number_of_songs(),
+            song_lengths.join(",")
+        ).map_err(|error| format!("Scan result could not be written -> {error}"))?;
+    }
+
+    eprintln!("\rScanned {} files, {sid_files} SID files found.", files.len());
+    Ok(())
+}
+
+fn select_song_to_analyze(player: &mut Player, song_title: Option<String>, song_number: i32) -> Result<(), String> {
+    let song_number = match song_title {
+        Some(song_title) => player.find_song_by_title(&song_title)
+            .ok_or(format!("No song found with a STIL title or name containing '{song_title}'."))?,
+        None => song_number
+    };
+    player.set_song_to_analyze(song_number)
+}
+
+fn resolve_clip(player: &Player, clip: Option<(TimePosition, TimePosition)>) -> Result<Option<(u32, u32)>, String> {
+    let Some((start_time, end_time)) = clip else {
+        return Ok(None);
+    };
+
+    let song_length = player.get_known_song_length(player.get_song_number());
+    let start_time = start_time.resolve(song_length)?;
+    let end_time = end_time.resolve(song_length)?;
+
+    if start_time >= end_time {
+        return Err("Clip start time must be before the end time.".to_string());
+    }
+    Ok(Some((start_time, end_time)))
 }
 
 fn print_device_names(device_names: Vec<String>) {
     if !device_names.is_empty() {
         println!("Available devices:");
         for (i, device_name) in device_names.iter().enumerate() {
-            println!("{:2}: {}", i + 1, device_name);
+            println!("{:2}: {}{}", i + 1, device_name, if i == 0 { " (default)" } else { "" });
         }
     } else {
         println!("No devices were found.");
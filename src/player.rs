@@ -5,57 +5,94 @@ pub mod sid_device;
 
 mod acid64_library;
 mod clock_adjust;
+mod digi_detector;
+mod fingerprint;
 mod hardsid_usb;
 mod hardsid_usb_device;
+mod loop_detector;
 mod network_sid_device;
+mod register_stats;
 mod sidblaster_usb_device;
 mod sidblaster_scheduler;
 mod sid_data_processor;
 mod sid_devices;
 mod sldb;
 mod stil;
+mod tee_sid_device;
 mod ultimate_device;
 
 use parking_lot::Mutex;
 use std::fs::read;
 use std::io::{Error, ErrorKind};
-use std::sync::atomic::{Ordering, AtomicI32};
+use std::sync::atomic::{Ordering, AtomicBool, AtomicI32};
 use std::sync::Arc;
 use std::{thread, time};
-use std::collections::VecDeque;
+use std::collections::{HashMap, VecDeque};
 use std::sync::mpsc::{sync_channel, Receiver, SyncSender};
 use thread_priority::{set_current_thread_priority, ThreadPriority};
 #[cfg(windows)]
 use windows::Win32::Media::{timeBeginPeriod, timeEndPeriod};
 
-use crate::utils::hvsc;
+use crate::utils::{hvsc, sid_file, sidblaster, song_number};
+use crate::utils::song_number::DEFAULT_SONG;
+use crate::utils::profile::PhaseTimer;
+use crate::utils::released::{self, ReleaseYear};
+use crate::utils::reglog::{RegisterLog, RegisterLogHeader, RegisterLogSource};
 use self::acid64_library::Acid64Library;
+pub use self::loop_detector::DetectedLoop;
+use self::network_sid_device::NetworkDeviceSettings;
+pub use self::network_sid_device::{DEFAULT_CLIENT_WAIT_CYCLES_THRESHOLD, DEFAULT_SOCKET_CONNECTION_TIMEOUT, DEFAULT_WRITE_CYCLES_THRESHOLD};
+use self::digi_detector::DigiDetector;
+use self::fingerprint::Fingerprint;
+use self::loop_detector::LoopDetector;
+use self::register_stats::RegisterStatistics;
 use self::sid_data_processor::{SidDataProcessor, SidWrite};
-use self::sid_device::{DeviceResponse, DUMMY_REG, SamplingMethod, SidClock, SidDevice, SidModel};
+use self::sid_device::{DeviceFeature, DeviceId, DeviceResponse, DUMMY_REG, SamplingMethod, SidClock, SidDevice, SidModel};
 use self::sid_devices::{SidDevices, SidDevicesFacade};
 use self::stil::Stil;
+use self::tee_sid_device::TeeSidDevice;
 use self::sldb::Sldb;
 
 const PAL_CYCLES_PER_SECOND: u32 = 312 * 63 * 50;
 const NTSC_CYCLES_PER_SECOND: u32 = 263 * 65 * 60;
+const NTSC_OLD_CYCLES_PER_SECOND: u32 = 262 * 64 * 60;
+const DREAN_CYCLES_PER_SECOND: u32 = 14_328_225 / 14;
 const ONE_MHZ_CYCLES_PER_SECOND: u32 = 1000000;
 
-const DEFAULT_HOST: &str = "127.0.0.1";
+pub const DEFAULT_HOST: &str = "127.0.0.1";
 const DEFAULT_PORT_NUMBER: &str = "6581";
 
+const MIN_NETWORK_FLUSH_CYCLES: u32 = 1000;
+const MAX_NETWORK_FLUSH_CYCLES: u32 = 100000;
+const MIN_NETWORK_THROTTLE_CYCLES: u32 = 2000;
+const MAX_NETWORK_THROTTLE_CYCLES: u32 = 1000000;
+
 const DEFAULT_ULTIMATE_HOST: &str = "";
 const DEFAULT_ULTIMATE_PORT_NUMBER: &str = "80";
 
+const MAX_CYCLES_SID_WRITE: u32 = 5 * PAL_CYCLES_PER_SECOND;
 const MIN_CYCLE_SID_WRITE: u32 = 8;
 const MIN_CYCLE_SID_WRITE_FAST_FORWARD: u32 = 8;
 
 const SID_MODEL_8580: i32 = 2;
+const SID_POSITION: i8 = 50;
+
+const MUS_FILE_TYPE: &str = "MUS";
+pub const MUS_TEXT_LINE_LENGTH: usize = 32;
+pub const MAX_VOLUME: u8 = 15;
 
 const BUSY_WAIT_MILLIS: u64 = 1;
-const PAUSE_SLEEP_MILLIS: u64 = 10;
+pub const DEFAULT_PAUSE_SLEEP_MILLIS: u64 = 50;
 const ABORT_DEVICE_DELAY_MILLIS: u64 = 20;
 
 const DEFAULT_SONG_LENGTH_IN_MILLIS: i32 = 300000;
+const DIGI_SCAN_SECONDS: u64 = 2;
+const DIGI_SCAN_TIMEOUT_MILLIS: u64 = 1000;
+const ESTIMATE_MAX_SECONDS: u64 = 15 * 60;
+const ESTIMATE_SILENCE_SECONDS: u64 = 5;
+const ESTIMATE_TIMEOUT_MILLIS: u64 = 5000;
+const SELF_TEST_SWEEP_STEPS: u32 = 100;
+const SELF_TEST_SWEEP_SECONDS: u32 = 2;
 
 pub const ABORT_NO: AbortType = 0;
 pub const ABORT_TO_QUIT: AbortType = 1;
@@ -70,7 +107,46 @@ pub enum PlayerCommand {
     Pause,
     Stop,
     EnableFastForward,
-    DisableFastForward
+    DisableFastForward,
+    Mute,
+    Unmute,
+    Restart,
+    StepFrame,
+    ToggleVolumeFix,
+    SetVolume(u8),
+    ReplaceHvscDatabases(Box<HvscDatabases>)
+}
+
+pub struct HvscDatabases {
+    sldb: Sldb,
+    stil: Stil
+}
+
+impl HvscDatabases {
+    pub fn load(hvsc_root: &str, load_stil: bool) -> Result<HvscDatabases, String> {
+        let mut sldb = Sldb::new();
+        sldb.load(hvsc_root)?;
+
+        let mut stil = Stil::new();
+        let stil_loaded = stil.load(hvsc_root);
+        if load_stil {
+            stil_loaded?;
+        }
+
+        Ok(HvscDatabases { sldb, stil })
+    }
+}
+
+pub enum PlayerQuery {
+    CurrentState(SyncSender<PlayerState>)
+}
+
+pub struct PlayerState {
+    pub time: u32,
+    pub song_number: i32,
+    pub paused: bool,
+    pub cpu_load: i32,
+    pub sid_registers: Vec<u8>
 }
 
 #[derive(Copy, Clone)]
@@ -101,9 +177,31 @@ impl SidCommand {
     }
 }
 
+// the commands of a silent run that are passed to an observer
+#[derive(Copy, Clone)]
+enum SilentRunEvent {
+    Cycles(u32),
+    Write(u32, u8, u8)
+}
+
+#[derive(Copy, Clone)]
+pub struct RunStatistics {
+    pub frames: u32,
+    pub cycles: u64,
+    pub sid_writes: u32
+}
+
 #[derive(Copy, Clone)]
 pub struct PlayerOutput {
     pub time: u32,
+    pub buffer_fill: Option<f32>
+}
+
+#[derive(Clone, Debug, PartialEq)]
+struct SidConfiguration {
+    device_numbers: Vec<i32>,
+    sid_models: Vec<i32>,
+    sid_clock: SidClock
 }
 
 pub struct Player {
@@ -113,27 +211,84 @@ pub struct Player {
     sid_data_processor: SidDataProcessor,
     filename: Option<String>,
     md5_hash: String,
+    sid_header: Option<Vec<u8>>,
     device_number: i32,
     device_numbers: Vec<i32>,
     song_number: i32,
     host_name_sid_device: String,
     port_sid_device: String,
+    connection_timeout_millis: u64,
+    ftdi_latency_millis: u64,
+    trace_protocol: bool,
+    mono_downmix: bool,
+    register_whitelist: Option<[bool; 256]>,
+    hardsid_busy_timeout_millis: Option<u64>,
+    network_cycle_thresholds: (u32, u32),
     host_name_ultimate: String,
     port_ultimate: String,
     abort_type: Arc<AtomicI32>,
     cmd_sender: SyncSender<PlayerCommand>,
     cmd_receiver: Receiver<PlayerCommand>,
+    query_sender: SyncSender<PlayerQuery>,
+    query_receiver: Receiver<PlayerQuery>,
     paused: bool,
     sid_written: bool,
     last_sid_write: [u8; 256],
     redo_buffer: VecDeque<SidWrite>,
     device_names: Arc<Mutex<Vec<String>>>,
     adjust_clock: bool,
+    c64_version: Option<i32>,
+    sid_clock: Option<SidClock>,
+    fast_switch: bool,
+    sids_configured: bool,
+    sid_configuration: Option<SidConfiguration>,
+    mirrored_sids: Vec<(u8, u8)>,
+    profile: bool,
+    tee_host_name: Option<String>,
+    step_cycles: Option<u64>,
+    step_remainder_cycles: u64,
+    step_paused: bool,
+    reset_on_exit: bool,
+    exiting: Arc<AtomicBool>,
+    buffer_meter: bool,
+    volume_fix: bool,
+    device_types: Vec<DeviceId>,
+    song_range: Option<(i32, i32)>,
+    estimate_length: bool,
+    estimated_song_lengths: HashMap<i32, Option<i32>>,
+    scanned_digi_type: Option<(i32, Option<String>)>,
+    sampling_method: Option<SamplingMethod>,
+    fade_in_millis: Option<u32>,
+    preferred_device_type: Option<DeviceId>,
+    sid_variant: Option<String>,
+    first_write_timer: Option<PhaseTimer>,
+    balance: i32,
+    swap_stereo: bool,
+    idle_timeout: Option<u32>,
+    reglog_filename: Option<String>,
+    register_log: Option<RegisterLog>,
+    pause_sleep_millis: u64,
+    seek_time: Option<u32>,
+    seek_accurate: bool,
+    muted: bool,
+    volume: u8,
+    digi_detector: DigiDetector,
+    register_stats: Option<RegisterStatistics>,
+    fingerprint: Option<Fingerprint>,
+    digi_detected: Arc<AtomicBool>,
+    delay_clamped: Arc<AtomicBool>,
+    loop_detector: Option<LoopDetector>,
+    stop_at_loop: bool,
+    detected_loop: Arc<Mutex<Option<DetectedLoop>>>,
+    skip_empty: Option<u32>,
+    song_empty: Arc<AtomicBool>,
+    timer_boost: bool,
     fast_forward_speed: i32,
     total_cycles: u32,
     output: Arc<Mutex<PlayerOutput>>,
     stil: Stil,
-    sldb: Sldb
+    sldb: Sldb,
+    hvsc_root: Option<String>
 }
 
 impl Drop for Player {
@@ -142,8 +297,10 @@ impl Drop for Player {
             self.acid64_lib.close_c64_instance(self.c64_instance);
         }
         #[cfg(windows)]
-        unsafe {
-            timeEndPeriod(1);
+        if self.timer_boost {
+            unsafe {
+                timeEndPeriod(1);
+            }
         }
     }
 }
@@ -151,12 +308,8 @@ impl Drop for Player {
 impl Player
 {
     pub fn new() -> Player {
-        #[cfg(windows)]
-        unsafe {
-            timeBeginPeriod(1);
-        }
-
         let (cmd_sender, cmd_receiver) = sync_channel(0);
+        let (query_sender, query_receiver) = sync_channel(1);
 
         let mut player_properties = Player {
             acid64_lib: Acid64Library::load().expect("acid64pro library could not be loaded"),
@@ -165,27 +318,84 @@ impl Player
             sid_data_processor: SidDataProcessor::new(),
             filename: None,
             md5_hash: "".to_string(),
+            sid_header: None,
             device_number: 0,
             device_numbers: vec![],
             song_number: 0,
             host_name_sid_device: DEFAULT_HOST.to_string(),
             port_sid_device: DEFAULT_PORT_NUMBER.to_string(),
+            connection_timeout_millis: DEFAULT_SOCKET_CONNECTION_TIMEOUT,
+            ftdi_latency_millis: sidblaster::DEFAULT_LATENCY_IN_MILLIS,
+            trace_protocol: false,
+            mono_downmix: false,
+            register_whitelist: None,
+            hardsid_busy_timeout_millis: None,
+            network_cycle_thresholds: (DEFAULT_WRITE_CYCLES_THRESHOLD, DEFAULT_CLIENT_WAIT_CYCLES_THRESHOLD),
             host_name_ultimate: DEFAULT_ULTIMATE_HOST.to_string(),
             port_ultimate: DEFAULT_ULTIMATE_PORT_NUMBER.to_string(),
             abort_type: Arc::new(AtomicI32::new(ABORT_NO)),
             cmd_sender,
             cmd_receiver,
+            query_sender,
+            query_receiver,
             paused: false,
             sid_written: false,
             last_sid_write: [0; 256],
             redo_buffer: VecDeque::new(),
             device_names: Arc::new(Mutex::new(Vec::new())),
             adjust_clock: false,
+            c64_version: None,
+            sid_clock: None,
+            fast_switch: false,
+            sids_configured: false,
+            sid_configuration: None,
+            mirrored_sids: vec![],
+            profile: false,
+            tee_host_name: None,
+            step_cycles: None,
+            step_remainder_cycles: 0,
+            step_paused: false,
+            reset_on_exit: true,
+            exiting: Arc::new(AtomicBool::new(false)),
+            buffer_meter: false,
+            volume_fix: true,
+            device_types: vec![DeviceId::HardsidUsb, DeviceId::SidBlaster, DeviceId::NetworkSidDevice, DeviceId::UltimateDevice],
+            preferred_device_type: None,
+            song_range: None,
+            estimate_length: false,
+            estimated_song_lengths: HashMap::new(),
+            scanned_digi_type: None,
+            sampling_method: None,
+            fade_in_millis: None,
+            sid_variant: None,
+            first_write_timer: None,
+            balance: 0,
+            swap_stereo: false,
+            idle_timeout: None,
+            reglog_filename: None,
+            register_log: None,
+            pause_sleep_millis: DEFAULT_PAUSE_SLEEP_MILLIS,
+            seek_time: None,
+            seek_accurate: false,
+            muted: false,
+            volume: MAX_VOLUME,
+            digi_detector: DigiDetector::new(),
+            register_stats: None,
+            fingerprint: None,
+            digi_detected: Arc::new(AtomicBool::new(false)),
+            delay_clamped: Arc::new(AtomicBool::new(false)),
+            loop_detector: None,
+            stop_at_loop: false,
+            detected_loop: Arc::new(Mutex::new(None)),
+            skip_empty: None,
+            song_empty: Arc::new(AtomicBool::new(false)),
+            timer_boost: false,
             fast_forward_speed: 1,
             total_cycles: 0,
-            output: Arc::new(Mutex::new(PlayerOutput { time: 0 })),
+            output: Arc::new(Mutex::new(PlayerOutput { time: 0, buffer_fill: None })),
             stil: Stil::new(),
-            sldb: Sldb::new()
+            sldb: Sldb::new(),
+            hvsc_root: None
         };
 
         player_properties.setup_c64_instance();
@@ -200,10 +410,24 @@ impl Player
         }
     }
 
+    pub fn enable_timer_boost(&mut self) {
+        if !self.timer_boost {
+            #[cfg(windows)]
+            unsafe {
+                timeBeginPeriod(1);
+            }
+            self.timer_boost = true;
+        }
+    }
+
     pub fn get_channel_sender(&self) -> SyncSender<PlayerCommand> {
         SyncSender::clone(&self.cmd_sender)
     }
 
+    pub fn get_query_sender(&self) -> SyncSender<PlayerQuery> {
+        SyncSender::clone(&self.query_sender)
+    }
+
     pub fn set_device_numbers(&mut self, device_numbers: Vec<i32>) {
         self.device_number = *device_numbers.first().unwrap_or(&-1);
 
@@ -214,6 +438,102 @@ impl Player
         self.host_name_sid_device = host_name;
     }
 
+    pub fn set_connection_timeout(&mut self, connection_timeout_millis: u64) {
+        self.connection_timeout_millis = connection_timeout_millis;
+    }
+
+    pub fn set_ftdi_latency(&mut self, ftdi_latency_millis: u64) {
+        self.ftdi_latency_millis = ftdi_latency_millis;
+    }
+
+    pub fn set_trace_protocol(&mut self, trace_protocol: bool) {
+        self.trace_protocol = trace_protocol;
+    }
+
+    pub fn set_mono_downmix(&mut self, mono_downmix: bool) {
+        self.mono_downmix = mono_downmix;
+    }
+
+    pub fn set_register_whitelist(&mut self, registers: &[u8]) {
+        self.register_whitelist = Some(Self::create_register_whitelist(registers));
+    }
+
+    fn create_register_whitelist(registers: &[u8]) -> [bool; 256] {
+        let mut register_whitelist = [false; 256];
+        for &reg in registers {
+            register_whitelist[reg as usize] = true;
+        }
+        register_whitelist
+    }
+
+    pub fn set_hardsid_busy_timeout(&mut self, busy_timeout_millis: u32) {
+        self.hardsid_busy_timeout_millis = Some(busy_timeout_millis as u64);
+    }
+
+    pub fn set_device_types(&mut self, enabled_device_types: Option<Vec<DeviceId>>, disabled_device_types: &[DeviceId]) -> Result<(), String> {
+        if let Some(enabled_device_types) = enabled_device_types {
+            self.device_types = enabled_device_types;
+        }
+        self.device_types.retain(|device_type| !disabled_device_types.contains(device_type));
+
+        if self.device_types.is_empty() {
+            return Err("At least one device type must be enabled.".to_string());
+        }
+        Ok(())
+    }
+
+    pub fn set_sampling_method(&mut self, sampling_method: SamplingMethod) {
+        self.sampling_method = Some(sampling_method);
+    }
+
+    pub fn set_fade_in(&mut self, fade_in_millis: u32) {
+        self.fade_in_millis = Some(fade_in_millis);
+    }
+
+    pub fn set_preferred_device_type(&mut self, device_type: DeviceId) {
+        self.preferred_device_type = Some(device_type);
+    }
+
+    pub fn set_reset_on_exit(&mut self, reset_on_exit: bool) {
+        self.reset_on_exit = reset_on_exit;
+    }
+
+    pub fn set_volume(&mut self, volume: u8) {
+        self.volume = volume.min(MAX_VOLUME);
+    }
+
+    pub fn get_volume(&self) -> u8 {
+        self.volume
+    }
+
+    pub fn set_buffer_meter(&mut self, buffer_meter: bool) {
+        self.buffer_meter = buffer_meter;
+    }
+
+    pub fn set_profile(&mut self, profile: bool) {
+        self.profile = profile;
+    }
+
+    pub fn set_network_cycle_thresholds(&mut self, flush_cycles: Option<u32>, throttle_cycles: Option<u32>) -> Result<(), String> {
+        let flush_cycles = flush_cycles.unwrap_or(DEFAULT_WRITE_CYCLES_THRESHOLD).clamp(MIN_NETWORK_FLUSH_CYCLES, MAX_NETWORK_FLUSH_CYCLES);
+        let throttle_cycles = throttle_cycles.unwrap_or(DEFAULT_CLIENT_WAIT_CYCLES_THRESHOLD).clamp(MIN_NETWORK_THROTTLE_CYCLES, MAX_NETWORK_THROTTLE_CYCLES);
+
+        if flush_cycles >= throttle_cycles {
+            return Err(format!("Network flush cycles ({flush_cycles}) must be lower than the network throttle cycles ({throttle_cycles})."));
+        }
+
+        self.network_cycle_thresholds = (flush_cycles, throttle_cycles);
+        Ok(())
+    }
+
+    pub fn set_sid_variant(&mut self, sid_variant: String) {
+        self.sid_variant = Some(sid_variant);
+    }
+
+    pub fn set_tee_host_name(&mut self, host_name: String) {
+        self.tee_host_name = Some(host_name);
+    }
+
     pub fn set_ultimate_device_host_name(&mut self, host_name: String) {
         self.host_name_ultimate = host_name;
     }
@@ -226,16 +546,60 @@ impl Player
         Arc::clone(&self.abort_type)
     }
 
+    pub fn get_digi_detected_ref(&self) -> Arc<AtomicBool> {
+        Arc::clone(&self.digi_detected)
+    }
+
+    pub fn get_delay_clamped_ref(&self) -> Arc<AtomicBool> {
+        Arc::clone(&self.delay_clamped)
+    }
+
+    pub fn get_song_empty_ref(&self) -> Arc<AtomicBool> {
+        Arc::clone(&self.song_empty)
+    }
+
+    pub fn get_exiting_ref(&self) -> Arc<AtomicBool> {
+        Arc::clone(&self.exiting)
+    }
+
+    pub fn get_detected_loop_ref(&self) -> Arc<Mutex<Option<DetectedLoop>>> {
+        Arc::clone(&self.detected_loop)
+    }
+
+    pub fn has_limited_digi_support(&mut self) -> bool {
+        let device_id = self.sid_device.as_mut().unwrap().get_device_id(self.device_number);
+        device_id == DeviceId::SidBlaster
+    }
+
+    pub fn is_balance_applied(&self) -> bool {
+        self.balance != 0 && self.get_number_of_sids() == 2
+    }
+
     pub fn play(&mut self) {
         let _ = set_current_thread_priority(ThreadPriority::Max);
 
         let cycles_per_second = self.get_cycles_per_second();
 
         let mut idle_count: u32 = 0;
+        let mut idle_cycles: u64 = 0;
+        let idle_timeout_cycles = self.idle_timeout.map(|seconds| seconds as u64 * cycles_per_second as u64);
+
+        let mut init_done = false;
+        let mut silent_cycles: Option<u64> = None;
+        let skip_empty_cycles = self.skip_empty.map(|seconds| seconds as u64 * cycles_per_second as u64);
+        self.song_empty.store(false, Ordering::SeqCst);
+
+        if let Some(loop_detector) = self.loop_detector.as_mut() {
+            loop_detector.init();
+        }
 
         self.total_cycles = 0;
         self.sid_written = false;
         self.paused = false;
+        self.step_cycles = None;
+        self.step_remainder_cycles = 0;
+        self.step_paused = false;
+        self.first_write_timer = self.profile.then(|| PhaseTimer::start(true));
         self.abort_type.store(ABORT_NO, Ordering::SeqCst);
 
         self.redo_buffer.clear();
@@ -249,13 +613,22 @@ impl Player
         self.sid_data_processor.init(0);
         self.sid_device.as_mut().unwrap().set_cycles_in_fifo(self.device_number, 0);
 
+        if let Some(seek_time) = self.seek_time {
+            if self.seek_accurate {
+                self.seek_time = None;
+                self.seek_accurately(seek_time, cycles_per_second);
+            } else {
+                self.acid64_lib.start_seek(self.c64_instance, seek_time);
+            }
+        }
+
         let mut device_state = DeviceResponse::Ok;
 
         while !self.should_quit() {
             self.process_player_command();
+            self.process_player_query();
 
             if self.paused {
-                thread::sleep(time::Duration::from_millis(PAUSE_SLEEP_MILLIS));
                 continue;
             }
 
@@ -287,18 +660,63 @@ impl Player
                 match sid_command {
                     SidCommand::Delay => {
                         device_state = self.process_sid_write(DUMMY_REG, 0);
+                        idle_cycles += self.total_cycles as u64;
+
+                        if let Some(cycles) = silent_cycles.as_mut() {
+                            *cycles += self.total_cycles as u64;
+                        }
                     },
                     SidCommand::Write => {
                         let reg = self.acid64_lib.get_register(self.c64_instance);
                         let data = self.acid64_lib.get_data(self.c64_instance);
 
                         device_state = self.process_sid_write(reg, data);
+
+                        if let Some(first_write_timer) = self.first_write_timer.take() {
+                            first_write_timer.stop("first SID write");
+                        }
                         idle_count = 0;
+                        idle_cycles = 0;
+
+                        if init_done {
+                            silent_cycles = None;
+                        }
                     },
                     SidCommand::Read => {
                         idle_count = 0;
+                        idle_cycles = 0;
+                    },
+                    SidCommand::InitDone => {
+                        if !init_done && skip_empty_cycles.is_some() {
+                            silent_cycles = Some(0);
+                        }
+                        init_done = true;
+                    },
+                    SidCommand::NextPart => {
+                        if let Some(register_log) = self.register_log.as_mut() {
+                            register_log.event("NEXTPART");
+                        }
+                    },
+                    SidCommand::SeekDone => {
+                        if let Some(seek_time) = self.seek_time.take() {
+                            self.sid_data_processor.set_time_in_millis(seek_time);
+                        }
+
+                        if let Some(register_log) = self.register_log.as_mut() {
+                            register_log.event("SEEKDONE");
+                        }
                     },
                     SidCommand::Idle => {
+                        idle_cycles += (cycles_per_second / 1000) as u64;
+
+                        if let Some(register_stats) = self.register_stats.as_mut() {
+                            register_stats.add_cycles(cycles_per_second / 1000);
+                        }
+
+                        if let Some(cycles) = silent_cycles.as_mut() {
+                            *cycles += (cycles_per_second / 1000) as u64;
+                        }
+
                         if self.sid_written {
                             idle_count += cycles_per_second / 1000;
 
@@ -310,15 +728,172 @@ impl Player
                     },
                     _ => (),
                 }
+
+                match sid_command {
+                    SidCommand::Delay | SidCommand::Write => {
+                        self.process_step(self.total_cycles as u64);
+                        self.process_loop_detection(self.total_cycles);
+                    },
+                    SidCommand::Idle => {
+                        self.process_step((cycles_per_second / 1000) as u64);
+                        self.process_loop_detection(cycles_per_second / 1000);
+                    },
+                    _ => ()
+                }
+
+                if idle_timeout_cycles.is_some_and(|timeout_cycles| idle_cycles >= timeout_cycles) {
+                    self.abort_type.store(ABORT_TO_QUIT, Ordering::SeqCst);
+                }
+
+                if silent_cycles.zip(skip_empty_cycles).is_some_and(|(cycles, empty_cycles)| cycles >= empty_cycles) {
+                    self.song_empty.store(true, Ordering::SeqCst);
+                    self.abort_type.store(ABORT_TO_QUIT, Ordering::SeqCst);
+                }
+            }
+        };
+
+        self.abort_playback();
+    }
+
+    pub fn play_register_log(&mut self, filename: &str) -> Result<(), String> {
+        let mut register_log_source = RegisterLogSource::open(filename)?;
+
+        self.init_devices()?;
+        self.configure_sid_device_for_register_log(register_log_source.get_header())?;
+
+        let _ = set_current_thread_priority(ThreadPriority::Max);
+
+        self.abort_type.store(ABORT_NO, Ordering::SeqCst);
+        self.sid_data_processor.init(0);
+        self.sid_device.as_mut().unwrap().set_cycles_in_fifo(self.device_number, 0);
+
+        let mut result = Ok(());
+
+        while !self.should_quit() {
+            match register_log_source.next_write() {
+                Ok(Some((cycles_real, reg, data))) => {
+                    let mut device_state = self.process_raw_sid_write(cycles_real, reg, data);
+
+                    while device_state == DeviceResponse::Busy && !self.should_quit() {
+                        self.update_player_output();
+
+                        if self.sid_data_processor.get_next_event_in_millis() >= 10 {
+                            thread::sleep(time::Duration::from_millis(BUSY_WAIT_MILLIS));
+                        }
+
+                        device_state = self.sid_device.as_mut().unwrap().retry_write(self.device_number);
+                    }
+                    self.update_player_output();
+                },
+                Ok(None) => {
+                    self.wait_until_buffer_played();
+                    break;
+                },
+                Err(error) => {
+                    result = Err(error);
+                    break;
+                }
+            }
+        }
+
+        self.abort_playback();
+        result
+    }
+
+    pub fn self_test(&mut self) -> Result<(), String> {
+        self.init_devices()?;
+        self.configure_sid_device_for_register_log(&RegisterLogHeader { number_of_sids: 1, sid_models: vec![0], ntsc: false })?;
+
+        self.sid_device.as_mut().unwrap().test_connection(self.device_number);
+        if !self.sid_device.as_mut().unwrap().is_connected(self.device_number) {
+            return Err(self.get_last_error().unwrap_or("Device is not connected.".to_string()));
+        }
+
+        self.abort_type.store(ABORT_NO, Ordering::SeqCst);
+        self.sid_data_processor.init(0);
+        self.sid_device.as_mut().unwrap().set_cycles_in_fifo(self.device_number, 0);
+
+        for (cycles_real, reg, data) in Self::get_self_test_writes() {
+            let mut device_state = self.process_raw_sid_write(cycles_real, reg, data);
+
+            while device_state == DeviceResponse::Busy && !self.should_quit() {
+                thread::sleep(time::Duration::from_millis(BUSY_WAIT_MILLIS));
+                self.update_player_output();
+                device_state = self.sid_device.as_mut().unwrap().retry_write(self.device_number);
             }
+            self.update_player_output();
+
+            if device_state == DeviceResponse::Error || self.should_quit() {
+                break;
+            }
+        }
+
+        self.wait_until_buffer_played();
+
+        let result = match self.get_last_error() {
+            Some(error) => Err(error),
+            None if !self.sid_device.as_mut().unwrap().is_connected(self.device_number) => Err("Device is disconnected during the self test.".to_string()),
+            None => Ok(())
         };
 
+        self.abort_playback();
+        result
+    }
+
+    // a rising frequency sweep with a triangle waveform on voice 1
+    fn get_self_test_writes() -> Vec<(u32, u8, u8)> {
+        let cycles_per_step = SELF_TEST_SWEEP_SECONDS * PAL_CYCLES_PER_SECOND / SELF_TEST_SWEEP_STEPS;
+
+        let mut writes = vec![
+            (MIN_CYCLE_SID_WRITE, 0x18, 0x0f),
+            (MIN_CYCLE_SID_WRITE, 0x05, 0x00),
+            (MIN_CYCLE_SID_WRITE, 0x06, 0xf0),
+            (MIN_CYCLE_SID_WRITE, 0x04, 0x11)
+        ];
+
+        for step in 0..SELF_TEST_SWEEP_STEPS {
+            let frequency = 0x0400 + step * 0x0100;
+            writes.push((cycles_per_step, 0x00, (frequency & 0xff) as u8));
+            writes.push((MIN_CYCLE_SID_WRITE, 0x01, (frequency >> 8) as u8));
+        }
+
+        writes.push((cycles_per_step, 0x04, 0x10));
+        writes
+    }
+
+    fn process_raw_sid_write(&mut self, cycles_real: u32, reg: u8, data: u8) -> DeviceResponse {
+        let cycles = self.adjust_cycles(cycles_real);
+
+        self.last_sid_write[reg as usize] = data;
+
+        self.sid_data_processor.write(cycles, reg, data, cycles_real);
+        let cycles_in_fifo = self.sid_data_processor.get_cycles_in_fifo();
+        self.sid_device.as_mut().unwrap().set_cycles_in_fifo(self.device_number, cycles_in_fifo);
+
+        self.write_to_sid(self.device_number, cycles, reg, data)
+    }
+
+    fn wait_until_buffer_played(&mut self) {
+        self.sid_device.as_mut().unwrap().force_flush(self.device_number);
+
+        while self.sid_data_processor.get_cycles_in_fifo() > 0 && !self.should_quit() {
+            thread::sleep(time::Duration::from_millis(BUSY_WAIT_MILLIS));
+            self.update_player_output();
+        }
+    }
+
+    fn abort_playback(&mut self) {
         self.abort_type.store(ABORTING, Ordering::SeqCst);
 
         if self.sid_device.as_mut().unwrap().is_connected(self.device_number) {
-            self.sid_device.as_mut().unwrap().reset_all_buffers(self.device_number);
+            self.sid_device.as_mut().unwrap().clear_all_buffers(self.device_number);
             thread::sleep(time::Duration::from_millis(ABORT_DEVICE_DELAY_MILLIS));
-            self.sid_device.as_mut().unwrap().silent_all_sids(self.device_number, true);
+
+            // the last register state is only kept when the program exits, switching songs always silences the SIDs
+            let keep_sid_state = !self.reset_on_exit && self.exiting.load(Ordering::SeqCst);
+            if !keep_sid_state {
+                self.sid_device.as_mut().unwrap().silent_all_sids(self.device_number, true);
+            }
         }
 
         self.fast_forward_speed = 1;
@@ -331,34 +906,33 @@ impl Player
             self.abort_type.store(ABORT_NO, Ordering::SeqCst);
         }
 
-        let recv_result = self.cmd_receiver.try_recv();
+        let recv_result = if self.paused {
+            self.cmd_receiver.recv_timeout(time::Duration::from_millis(self.pause_sleep_millis)).ok()
+        } else {
+            self.cmd_receiver.try_recv().ok()
+        };
 
-        if let Ok(result) = recv_result {
+        if let Some(result) = recv_result {
             match result {
                 PlayerCommand::Play => {
-                    if self.paused {
-                        self.sid_device.as_mut().unwrap().reset_active_sids(self.device_number);
-                        self.reactivate_voices();
-                        self.sid_device.as_mut().unwrap().force_flush(self.device_number);
-
-                        self.rewrite_buffer();
-
-                        if self.sid_device.as_mut().unwrap().has_remote_sidplayer(self.device_number) {
-                            if let Some(filename) = self.filename.clone() {
-                                let _ = self.restart_song();
-                                self.send_sid(&filename, self.song_number);
-                            }
-                        }
-                    }
-                    self.paused = false;
+                    self.step_cycles = None;
+                    self.step_remainder_cycles = 0;
+                    self.resume_playback();
+                },
+                PlayerCommand::StepFrame => {
+                    self.step_cycles = Some(Self::get_cycles_to_next_frame(self.get_cycles_per_frame() as u64, self.step_remainder_cycles));
+                    self.resume_playback();
                 },
                 PlayerCommand::Pause => {
                     let device = self.sid_device.as_mut().unwrap();
-                    device.reset_all_buffers(self.device_number);
+                    device.clear_all_buffers(self.device_number);
                     device.silent_all_sids(self.device_number, false);
 
                     self.stop_player();
 
+                    self.step_cycles = None;
+                    self.step_remainder_cycles = 0;
+                    self.step_paused = false;
                     self.paused = true;
                 },
                 PlayerCommand::EnableFastForward => {
@@ -367,11 +941,109 @@ impl Player
                 PlayerCommand::DisableFastForward => {
                     self.disable_fast_forward();
                 },
+                PlayerCommand::Mute => {
+                    self.muted = true;
+                    self.sid_device.as_mut().unwrap().silent_all_sids(self.device_number, true);
+                },
+                PlayerCommand::Unmute => {
+                    self.muted = false;
+                    self.write_volume_registers();
+                },
+                PlayerCommand::Restart => {
+                    if self.restart_song().is_ok() && self.sid_device.as_mut().unwrap().has_remote_sidplayer(self.device_number) {
+                        if let Some(filename) = self.filename.clone() {
+                            self.send_sid(&filename, self.song_number);
+                        }
+                    }
+                    self.step_cycles = None;
+                    self.step_remainder_cycles = 0;
+                    self.step_paused = false;
+                    self.paused = false;
+                },
+                PlayerCommand::ToggleVolumeFix => {
+                    self.volume_fix = !self.volume_fix;
+                    self.acid64_lib.enable_volume_fix(self.c64_instance, self.volume_fix);
+                },
+                PlayerCommand::SetVolume(volume) => {
+                    self.volume = volume.min(MAX_VOLUME);
+                    self.write_volume_registers();
+                },
+                PlayerCommand::ReplaceHvscDatabases(hvsc_databases) => {
+                    self.sldb = hvsc_databases.sldb;
+                    self.stil = hvsc_databases.stil;
+                },
                 _ => ()
             }
         }
     }
 
+    fn resume_playback(&mut self) {
+        if self.paused && !self.step_paused {
+            self.sid_device.as_mut().unwrap().reset_active_sids(self.device_number);
+            self.reactivate_voices();
+            self.sid_device.as_mut().unwrap().force_flush(self.device_number);
+
+            self.rewrite_buffer();
+
+            if self.sid_device.as_mut().unwrap().has_remote_sidplayer(self.device_number) {
+                if let Some(filename) = self.filename.clone() {
+                    let _ = self.restart_song();
+                    self.send_sid(&filename, self.song_number);
+                }
+            }
+        }
+        self.step_paused = false;
+        self.paused = false;
+    }
+
+    fn process_step(&mut self, cycles: u64) {
+        if let Some(step_cycles) = self.step_cycles {
+            if cycles >= step_cycles {
+                // a write or idle can run past the frame boundary, carry it so the next step ends on a frame boundary
+                self.step_remainder_cycles = (cycles - step_cycles) % self.get_cycles_per_frame() as u64;
+                self.step_cycles = None;
+                self.sid_device.as_mut().unwrap().force_flush(self.device_number);
+                self.step_paused = true;
+                self.paused = true;
+            } else {
+                self.step_cycles = Some(step_cycles - cycles);
+            }
+        }
+    }
+
+    fn get_cycles_to_next_frame(cycles_per_frame: u64, remainder_cycles: u64) -> u64 {
+        cycles_per_frame - remainder_cycles % cycles_per_frame
+    }
+
+    fn process_loop_detection(&mut self, cycles: u32) {
+        let detected_loop = self.loop_detector.as_mut().and_then(|loop_detector| loop_detector.add_cycles(cycles, &self.last_sid_write));
+
+        if let Some(detected_loop) = detected_loop {
+            *self.detected_loop.lock() = Some(detected_loop);
+
+            if self.stop_at_loop {
+                self.abort_type.store(ABORT_TO_QUIT, Ordering::SeqCst);
+            }
+        }
+    }
+
+    fn process_player_query(&mut self) {
+        if let Ok(PlayerQuery::CurrentState(state_sender)) = self.query_receiver.try_recv() {
+            let number_of_sids = self.acid64_lib.get_number_of_sids(self.c64_instance);
+            let sid_registers = (0..number_of_sids as usize * 0x20)
+                .map(|reg| self.sid_data_processor.get_last_sid_write(reg as u8))
+                .collect();
+
+            let _ = state_sender.try_send(PlayerState {
+                time: self.sid_data_processor.get_time_in_millis(),
+                song_number: self.song_number,
+                paused: self.paused,
+                cpu_load: self.acid64_lib.get_cpu_load(self.c64_instance),
+                sid_registers
+            });
+        }
+    }
+
     fn process_redo_buffer(&mut self) -> DeviceResponse {
         let mut total_cycles = 0;
 
@@ -475,13 +1147,18 @@ impl Player
 
         self.acid64_lib.set_song_to_play(self.c64_instance, self.song_number);
 
+        Self::convert_song_lengths_to_bcd(&song_lengths_in_millis)
+    }
+
+    fn convert_song_lengths_to_bcd(song_lengths_in_millis: &[i32]) -> Vec<u8> {
         let mut song_lengths_in_bcd = vec![];
         for song_length in song_lengths_in_millis {
-            let seconds_total = (song_length + 500) / 1000;
+            // two BCD digits can hold up to 99:59, longer songs are capped
+            let seconds_total = ((song_length + 500) / 1000).min(99 * 60 + 59);
             let seconds = seconds_total % 60;
             let seconds = Self::int_to_bcd(seconds);
 
-            let minutes = seconds_total / 60 % 100;
+            let minutes = seconds_total / 60;
             let minutes = Self::int_to_bcd(minutes);
             song_lengths_in_bcd.push(minutes as u8);
             song_lengths_in_bcd.push(seconds as u8);
@@ -508,8 +1185,15 @@ impl Player
     pub fn update_player_output(&mut self) {
         self.sid_data_processor.process_sid_write_fifo();
 
+        let buffer_fill = if self.buffer_meter {
+            self.sid_device.as_mut().unwrap().get_buffer_fill(self.device_number)
+        } else {
+            None
+        };
+
         let mut output = self.output.lock();
         output.time = self.sid_data_processor.get_time_in_millis();
+        output.buffer_fill = buffer_fill;
     }
 
     fn is_aborted_for_command(&self) -> bool {
@@ -566,6 +1250,8 @@ impl Player
         match device_clock {
             SidClock::Pal => PAL_CYCLES_PER_SECOND,
             SidClock::Ntsc => NTSC_CYCLES_PER_SECOND,
+            SidClock::NtscOld => NTSC_OLD_CYCLES_PER_SECOND,
+            SidClock::Drean => DREAN_CYCLES_PER_SECOND,
             SidClock::OneMhz => ONE_MHZ_CYCLES_PER_SECOND
         }
     }
@@ -575,19 +1261,32 @@ impl Player
     }
 
     pub fn get_song_length(&self, song_number: i32) -> i32 {
-        self.sldb.get_song_length(&self.md5_hash, song_number).unwrap_or(DEFAULT_SONG_LENGTH_IN_MILLIS)
+        self.get_known_song_length(song_number).unwrap_or(DEFAULT_SONG_LENGTH_IN_MILLIS)
     }
 
-    pub fn get_filename(&self) -> Option<String> {
-        self.filename.clone()
+    pub fn get_known_song_length(&self, song_number: i32) -> Option<i32> {
+        self.sldb.get_song_length(&self.md5_hash, song_number)
     }
 
-    pub fn get_sid_model(&self) -> i32 {
+    // an estimate is a guess that is only meant for display, it's never used as a song length of the database
+    pub fn get_estimated_song_length(&self, song_number: i32) -> Option<i32> {
+        self.estimated_song_lengths.get(&song_number).copied().flatten().filter(|&song_length| song_length > 0)
+    }
+
+    pub fn get_filename(&self) -> Option<String> {
+        self.filename.clone()
+    }
+
+    pub fn get_sid_model(&self) -> i32 {
         self.acid64_lib.get_sid_model(self.c64_instance, 0)
     }
 
     pub fn get_c64_version(&self) -> i32 {
-        self.acid64_lib.get_c64_version(self.c64_instance)
+        self.c64_version.unwrap_or_else(|| self.acid64_lib.get_c64_version(self.c64_instance))
+    }
+
+    pub fn get_md5_hash(&self) -> String {
+        self.md5_hash.clone()
     }
 
     pub fn get_title(&self) -> String {
@@ -602,6 +1301,24 @@ impl Player
         self.acid64_lib.get_released(self.c64_instance)
     }
 
+    pub fn get_release_year(&self) -> Option<ReleaseYear> {
+        released::parse_release_year(&self.get_released())
+    }
+
+    pub fn get_mus_text(&self) -> Option<[u8; 32*5]> {
+        if self.acid64_lib.get_file_type(self.c64_instance) != MUS_FILE_TYPE {
+            return None;
+        }
+
+        let mut mus_text = [0; 32*5];
+        self.acid64_lib.get_mus_text(self.c64_instance, &mut mus_text);
+        Some(mus_text)
+    }
+
+    pub fn get_hvsc_filename(&self) -> Option<String> {
+        self.sldb.get_hvsc_filename(&self.md5_hash)
+    }
+
     pub fn get_stil_entry(&self) -> Option<String> {
         let hvsc_filename = self.sldb.get_hvsc_filename(&self.md5_hash);
 
@@ -611,6 +1328,18 @@ impl Player
         None
     }
 
+    pub fn find_song_by_title(&self, title: &str) -> Option<i32> {
+        let title = title.to_lowercase();
+
+        Stil::get_song_titles(&self.get_stil_entry()?).into_iter()
+            .find(|(_, song_title)| song_title.to_lowercase().contains(&title))
+            .map(|(song_number, _)| song_number)
+    }
+
+    pub fn is_stil_loaded(&self) -> bool {
+        self.stil.is_loaded()
+    }
+
     pub fn get_device_numbers(&self) -> Vec<i32> {
         self.device_numbers.clone()
     }
@@ -627,6 +1356,55 @@ impl Player
         self.sid_device.as_mut().unwrap().get_device_info(device_number).name
     }
 
+    pub fn has_sid_model_mismatch(&mut self, sid_nr: i32) -> bool {
+        let sid_model = self.acid64_lib.get_sid_model(self.c64_instance, sid_nr);
+        let device_number = *self.device_numbers.get(sid_nr as usize).unwrap_or(&0);
+        let device_name = self.get_device_info(device_number);
+
+        match sid_model {
+            1 => device_name.contains("8580") && !device_name.contains("6581"),
+            SID_MODEL_8580 => device_name.contains("6581") && !device_name.contains("8580"),
+            _ => false
+        }
+    }
+
+    pub fn get_unsupported_features(&mut self) -> Vec<&'static str> {
+        let mut unsupported_features = vec![];
+
+        if self.get_number_of_sids() > 1 {
+            let sid_device = self.sid_device.as_mut().unwrap();
+
+            if !sid_device.is_feature_supported(self.device_number, DeviceFeature::MultipleSids) {
+                unsupported_features.push("multiple SID chips (only the first SID is played)");
+            } else if !sid_device.is_feature_supported(self.device_number, DeviceFeature::SidPosition) {
+                unsupported_features.push("stereo panning");
+            }
+
+            if self.swap_stereo && !sid_device.is_feature_supported(self.device_number, DeviceFeature::SidPosition) {
+                unsupported_features.push("--swap-stereo");
+            }
+        }
+
+        let sid_device = self.sid_device.as_mut().unwrap();
+
+        if self.sampling_method.is_some() && !sid_device.is_feature_supported(self.device_number, DeviceFeature::SamplingMethod) {
+            unsupported_features.push("--quality");
+        }
+
+        match self.sid_clock {
+            Some(SidClock::NtscOld | SidClock::Drean) if !sid_device.is_feature_supported(self.device_number, DeviceFeature::ExtendedSidClock) => {
+                unsupported_features.push("NTSC-old and Drean clock (NTSC or PAL clock is used)");
+            },
+            Some(_) if !sid_device.is_feature_supported(self.device_number, DeviceFeature::SidClock) => unsupported_features.push("-C"),
+            _ => ()
+        }
+
+        if self.fade_in_millis.is_some() && !sid_device.is_feature_supported(self.device_number, DeviceFeature::FadeIn) {
+            unsupported_features.push("--fade-in");
+        }
+        unsupported_features
+    }
+
     pub fn has_remote_sidplayer(&mut self) -> bool {
         self.sid_device.as_mut().unwrap().has_remote_sidplayer(self.device_number)
     }
@@ -641,59 +1419,435 @@ impl Player
         }
 
         if let Some(hvsc_root) = hvsc_root {
+            let timer = PhaseTimer::start(self.profile);
             self.sldb.load(&hvsc_root)?;
+            timer.stop("SLDB load");
 
+            let timer = PhaseTimer::start(self.profile);
+            let stil_loaded = self.stil.load(&hvsc_root);
+            timer.stop("STIL load");
             if load_stil {
-                self.stil.load(&hvsc_root)?;
+                stil_loaded?;
             }
+
+            self.hvsc_root = Some(hvsc_root);
         }
         Ok(())
     }
 
+    pub fn get_hvsc_root(&self) -> Option<String> {
+        self.hvsc_root.clone()
+    }
+
     pub fn set_adjust_clock(&mut self, adjust_clock: bool) {
         self.adjust_clock = adjust_clock;
     }
 
+    pub fn set_c64_version(&mut self, c64_version: i32) {
+        self.c64_version = Some(c64_version);
+    }
+
+    pub fn set_sid_clock(&mut self, sid_clock: SidClock) {
+        self.sid_clock = Some(sid_clock);
+
+        if self.c64_version.is_none() {
+            self.c64_version = match sid_clock {
+                SidClock::Ntsc | SidClock::NtscOld => Some(2),
+                _ => Some(1)
+            };
+        }
+    }
+
+    pub fn set_fast_switch(&mut self, fast_switch: bool) {
+        self.fast_switch = fast_switch;
+    }
+
+    pub fn set_estimate_length(&mut self, estimate_length: bool) {
+        self.estimate_length = estimate_length;
+    }
+
+    pub fn set_balance(&mut self, balance: i32) {
+        self.balance = balance;
+    }
+
+    pub fn set_swap_stereo(&mut self, swap_stereo: bool) {
+        self.swap_stereo = swap_stereo;
+    }
+
+    pub fn is_stereo_swapped(&self) -> bool {
+        self.swap_stereo
+    }
+
+    fn get_sid_position(&self) -> i8 {
+        if self.swap_stereo {
+            -SID_POSITION
+        } else {
+            SID_POSITION
+        }
+    }
+
+    pub fn set_idle_timeout(&mut self, idle_timeout_in_seconds: u32) {
+        self.idle_timeout = Some(idle_timeout_in_seconds);
+    }
+
+    pub fn set_reglog_filename(&mut self, filename: String) {
+        self.reglog_filename = Some(filename);
+    }
+
+    pub fn set_pause_sleep(&mut self, pause_sleep_millis: u64) {
+        self.pause_sleep_millis = pause_sleep_millis;
+    }
+
+    pub fn set_skip_empty(&mut self, skip_empty_seconds: u32) {
+        self.skip_empty = Some(skip_empty_seconds);
+    }
+
+    pub fn set_seek_time(&mut self, seek_time_in_millis: u32) {
+        self.seek_time = Some(seek_time_in_millis);
+    }
+
+    pub fn set_seek_accurate(&mut self, seek_accurate: bool) {
+        self.seek_accurate = seek_accurate;
+    }
+
+    pub fn enable_register_stats(&mut self) {
+        let cycles_per_frame = self.get_cycles_per_frame();
+        self.register_stats = Some(RegisterStatistics::new(cycles_per_frame));
+    }
+
+    pub fn enable_loop_detection(&mut self, stop_at_loop: bool) {
+        let cycles_per_second = self.get_tune_cycles_per_second();
+        self.loop_detector = Some(LoopDetector::new(self.get_cycles_per_frame(), cycles_per_second));
+        self.stop_at_loop = stop_at_loop;
+    }
+
+    fn get_tune_cycles_per_second(&self) -> u32 {
+        Self::tune_cycles_per_second(self.get_c64_version())
+    }
+
+    fn tune_cycles_per_second(c64_version: i32) -> u32 {
+        match c64_version {
+            2 => NTSC_CYCLES_PER_SECOND,
+            _ => PAL_CYCLES_PER_SECOND
+        }
+    }
+
+    fn get_cycles_per_frame(&self) -> u32 {
+        Self::cycles_per_frame(self.get_c64_version())
+    }
+
+    fn cycles_per_frame(c64_version: i32) -> u32 {
+        match c64_version {
+            2 => NTSC_CYCLES_PER_SECOND / 60,
+            _ => PAL_CYCLES_PER_SECOND / 50
+        }
+    }
+
+    fn frames_to_cycles(frames: u32, c64_version: i32) -> u64 {
+        frames as u64 * Self::cycles_per_frame(c64_version) as u64
+    }
+
+    pub fn get_register_stats_report(&self) -> Option<String> {
+        let cycles_per_second = self.get_tune_cycles_per_second();
+        let number_of_sids = self.get_number_of_sids();
+
+        self.register_stats.as_ref().map(|register_stats| register_stats.get_report(cycles_per_second, number_of_sids))
+    }
+
+    pub fn run_frames(&mut self, frames: u32) -> RunStatistics {
+        let cycles_per_second = self.get_tune_cycles_per_second();
+        let cycles_per_frame = self.get_cycles_per_frame();
+
+        let mut cycles_to_run = Self::frames_to_cycles(frames, self.get_c64_version());
+        if let Some(song_length) = self.get_known_song_length(self.song_number) {
+            cycles_to_run = cycles_to_run.min(song_length as u64 * cycles_per_second as u64 / 1000);
+        }
+
+        let (cycles, sid_writes) = self.run_silently(cycles_to_run, cycles_per_second, true);
+
+        RunStatistics {
+            frames: (cycles / cycles_per_frame as u64) as u32,
+            cycles,
+            sid_writes
+        }
+    }
+
+    pub fn create_fingerprint(&mut self, filename: &str) -> Result<(), String> {
+        let cycles_per_second = self.get_tune_cycles_per_second();
+        let song_length = self.get_song_length(self.song_number);
+
+        self.fingerprint = Some(Fingerprint::new(cycles_per_second, self.get_number_of_sids()));
+        self.run_silently(song_length as u64 * cycles_per_second as u64 / 1000, cycles_per_second, false);
+
+        let fingerprint = self.fingerprint.take().unwrap();
+        self.init_song(self.song_number);
+        fingerprint.write_svg(filename)
+    }
+
+    fn run_silently(&mut self, cycles_to_run: u64, cycles_per_second: u32, stop_at_restart: bool) -> (u64, u32) {
+        self.run_silently_with(cycles_to_run, cycles_per_second, stop_at_restart, None, &mut |_| false)
+    }
+
+    // the observer gets the cycles of every delay and every write, it stops the run by returning true
+    fn run_silently_with(&mut self, cycles_to_run: u64, cycles_per_second: u32, stop_at_restart: bool, timeout_millis: Option<u64>,
+                         observer: &mut dyn FnMut(SilentRunEvent) -> bool) -> (u64, u32) {
+        let start_time = time::Instant::now();
+        let mut cycles: u64 = 0;
+        let mut sid_writes = 0;
+        let mut init_done = false;
+
+        while cycles < cycles_to_run && !self.should_quit() {
+            if timeout_millis.is_some_and(|timeout_millis| start_time.elapsed().as_millis() as u64 >= timeout_millis) {
+                break;
+            }
+
+            self.acid64_lib.run(self.c64_instance);
+
+            let stop = match SidCommand::from_integer(self.acid64_lib.get_command(self.c64_instance)) {
+                SidCommand::Delay => {
+                    let delay_cycles = self.acid64_lib.get_cycles(self.c64_instance);
+                    cycles += delay_cycles as u64;
+
+                    if let Some(register_stats) = self.register_stats.as_mut() {
+                        register_stats.add_cycles(delay_cycles as u32);
+                    }
+
+                    if let Some(fingerprint) = self.fingerprint.as_mut() {
+                        fingerprint.add_cycles(delay_cycles as u32);
+                    }
+                    observer(SilentRunEvent::Cycles(delay_cycles as u32))
+                },
+                SidCommand::Write => {
+                    let write_cycles = self.acid64_lib.get_cycles(self.c64_instance);
+                    cycles += write_cycles as u64;
+
+                    let reg = self.acid64_lib.get_register(self.c64_instance);
+                    let data = self.acid64_lib.get_data(self.c64_instance);
+                    self.last_sid_write[reg as usize] = data;
+                    sid_writes += 1;
+
+                    if let Some(register_stats) = self.register_stats.as_mut() {
+                        register_stats.process_write(write_cycles as u32, reg, data);
+                    }
+
+                    if let Some(fingerprint) = self.fingerprint.as_mut() {
+                        fingerprint.process_write(write_cycles as u32, reg);
+                    }
+                    observer(SilentRunEvent::Write(write_cycles as u32, reg, data))
+                },
+                SidCommand::Idle => {
+                    cycles += (cycles_per_second / 1000) as u64;
+
+                    if let Some(register_stats) = self.register_stats.as_mut() {
+                        register_stats.add_cycles(cycles_per_second / 1000);
+                    }
+
+                    if let Some(fingerprint) = self.fingerprint.as_mut() {
+                        fingerprint.add_cycles(cycles_per_second / 1000);
+                    }
+                    observer(SilentRunEvent::Cycles(cycles_per_second / 1000))
+                },
+                SidCommand::InitDone => {
+                    // a next init means that the tune restarted, which ends the run
+                    let restarted = stop_at_restart && init_done;
+                    init_done = true;
+                    restarted
+                },
+                _ => false
+            };
+
+            if stop {
+                break;
+            }
+        }
+        (cycles, sid_writes)
+    }
+
+    // an analysis doesn't add to the register statistics and fingerprint, and the song is restarted afterwards
+    fn analyze_silently(&mut self, cycles_to_run: u64, cycles_per_second: u32, timeout_millis: u64, observer: &mut dyn FnMut(SilentRunEvent) -> bool) {
+        let register_stats = self.register_stats.take();
+        let fingerprint = self.fingerprint.take();
+        let last_sid_write = self.last_sid_write;
+
+        self.run_silently_with(cycles_to_run, cycles_per_second, false, Some(timeout_millis), observer);
+
+        self.last_sid_write = last_sid_write;
+        self.register_stats = register_stats;
+        self.fingerprint = fingerprint;
+        self.init_song(self.song_number);
+    }
+
+    fn seek_accurately(&mut self, seek_time_in_millis: u32, cycles_per_second: u32) {
+        self.run_silently(seek_time_in_millis as u64 * cycles_per_second as u64 / 1000, cycles_per_second, false);
+
+        self.sid_data_processor.set_time_in_millis(seek_time_in_millis);
+        self.restore_sid_registers();
+
+        if let Some(register_log) = self.register_log.as_mut() {
+            register_log.event("SEEKDONE");
+        }
+    }
+
+    fn restore_sid_registers(&mut self) {
+        let number_of_sids = self.acid64_lib.get_number_of_sids(self.c64_instance);
+
+        for sid_nr in 0..number_of_sids {
+            let sid_base = (sid_nr * 0x20) as u8;
+
+            for reg in (0x00..=0x17).filter(|reg| ![0x04, 0x0b, 0x12].contains(reg)) {
+                self.write_last_sid_write_direct(sid_base + reg);
+            }
+
+            self.write_last_sid_write_direct(sid_base + 0x04);
+            self.write_last_sid_write_direct(sid_base + 0x0b);
+            self.write_last_sid_write_direct(sid_base + 0x12);
+            self.write_last_sid_write_direct(sid_base + 0x18);
+        }
+        self.sid_device.as_mut().unwrap().force_flush(self.device_number);
+    }
+
     pub fn init_devices(&mut self) -> Result<(), String> {
         if self.sid_device.is_none() {
-            let mut devices = SidDevices::new(Arc::clone(&self.abort_type))
-                .connect_hardsid_device()
-                .connect_sidblaster()
-                .connect_network_device(&self.host_name_sid_device, &self.port_sid_device)
-                .connect_ultimate_device(&self.host_name_ultimate, &self.port_ultimate);
+            let mut devices = SidDevices::new(Arc::clone(&self.abort_type));
+
+            if self.device_types.contains(&DeviceId::HardsidUsb) {
+                devices = devices.connect_hardsid_device(self.mono_downmix, self.hardsid_busy_timeout_millis);
+            }
+
+            if self.device_types.contains(&DeviceId::SidBlaster) {
+                devices = devices.connect_sidblaster(self.ftdi_latency_millis, self.mono_downmix);
+            }
+
+            if self.device_types.contains(&DeviceId::NetworkSidDevice) {
+                devices = devices.connect_network_device(&self.host_name_sid_device, &self.port_sid_device, &self.get_network_device_settings());
+            }
+
+            if self.device_types.contains(&DeviceId::UltimateDevice) {
+                devices = devices.connect_ultimate_device(&self.host_name_ultimate, &self.port_ultimate);
+            }
 
             if !devices.has_devices() && devices.has_errors() {
                 return Err(devices.errors());
             }
 
+            if let Some(preferred_device_type) = self.preferred_device_type {
+                devices.prefer_device_type(preferred_device_type);
+            }
+
             devices.set_native_device_clock(!self.adjust_clock);
+            devices.set_balance(self.balance);
+
+            let sid_device = Box::new(SidDevicesFacade{ devices });
 
-            let sid_device = SidDevicesFacade{ devices };
-            self.sid_device = Some(Box::new(sid_device));
+            if let Some(tee_host_name) = self.tee_host_name.clone() {
+                let tee_device = self.connect_tee_device(&tee_host_name)?;
+                self.sid_device = Some(Box::new(TeeSidDevice::new(sid_device, tee_device)));
+            } else {
+                self.sid_device = Some(sid_device);
+            }
+            self.sid_configuration = None;
 
             self.refresh_device_names();
         }
         Ok(())
     }
 
+    fn get_network_device_settings(&self) -> NetworkDeviceSettings {
+        let (write_cycles_threshold, client_wait_cycles_threshold) = self.network_cycle_thresholds;
+
+        NetworkDeviceSettings {
+            connection_timeout_millis: self.connection_timeout_millis,
+            trace_protocol: self.trace_protocol,
+            write_cycles_threshold,
+            client_wait_cycles_threshold,
+            sid_variant: self.sid_variant.clone(),
+            device_number_given: self.device_numbers.iter().any(|&device_number| device_number != -1)
+        }
+    }
+
+    fn connect_tee_device(&self, host_name: &str) -> Result<Box<dyn SidDevice + Send>, String> {
+        let mut devices = SidDevices::new(Arc::clone(&self.abort_type))
+            .connect_network_device(host_name, DEFAULT_PORT_NUMBER, &self.get_network_device_settings());
+
+        if !devices.has_devices() {
+            return Err(format!("Tee device {host_name} is not available.\n{}", devices.errors()));
+        }
+
+        devices.set_native_device_clock(!self.adjust_clock);
+        Ok(Box::new(SidDevicesFacade{ devices }))
+    }
+
     pub fn load_file(&mut self, filename: &str) -> Result<(), String> {
+        self.load_file_data(filename)?;
+
+        self.init_devices()?;
+        self.configure_sid_device(false)?;
+        self.create_register_log()?;
+        self.set_song_to_play(DEFAULT_SONG)
+    }
+
+    pub fn load_file_data(&mut self, filename: &str) -> Result<(), String> {
+        let data = read(filename).map_err(|error| format!("File '{filename}' could not be read -> {error}"))?;
+        if !sid_file::is_valid_file(&data) {
+            return Err(format!("File '{filename}' is not a valid SID file."));
+        }
+
+        if sid_file::get_number_of_songs(&data) == Some(0) {
+            return Err(format!("File '{filename}' has no playable sub tunes."));
+        }
+
+        let timer = PhaseTimer::start(self.profile);
         let is_loaded = self.acid64_lib.load_file(self.c64_instance, filename);
+        timer.stop("file load");
 
         if !is_loaded {
             Err(format!("File '{filename}' could not be loaded."))
+        } else if self.acid64_lib.get_number_of_songs(self.c64_instance) <= 0 {
+            Err(format!("File '{filename}' has no playable sub tunes."))
         } else {
             self.filename = Some(filename.to_string());
+            self.sid_header = sid_file::get_sid_header(&data);
+            self.song_range = None;
+            self.estimated_song_lengths.clear();
+            self.scanned_digi_type = None;
+
+            self.digi_detector.init();
+            self.digi_detected.store(false, Ordering::Relaxed);
 
+            // the frame rate can differ per file
+            if self.register_stats.is_some() {
+                self.enable_register_stats();
+            }
+
+            if self.loop_detector.is_some() {
+                self.enable_loop_detection(self.stop_at_loop);
+            }
+
+            let timer = PhaseTimer::start(self.profile);
             if self.sldb.is_new_md5_hash_used() {
                 self.md5_hash = self.acid64_lib.get_md5_hash(self.c64_instance);
             } else {
                 self.md5_hash = self.acid64_lib.get_ancient_md5_hash(self.c64_instance);
             }
+            timer.stop("MD5 hashing");
+            Ok(())
+        }
+    }
 
-            self.init_devices()?;
-            self.configure_sid_device(false)?;
-            self.set_song_to_play(-1)
+    fn create_register_log(&mut self) -> Result<(), String> {
+        self.register_log = None;
+
+        if let Some(reglog_filename) = self.reglog_filename.as_ref() {
+            let number_of_sids = self.get_number_of_sids();
+            let header = RegisterLogHeader {
+                number_of_sids,
+                sid_models: (0..number_of_sids).map(|sid_nr| self.acid64_lib.get_sid_model(self.c64_instance, sid_nr)).collect(),
+                ntsc: self.get_c64_version() == 2
+            };
+
+            self.register_log = Some(RegisterLog::create(reglog_filename, &header)?);
         }
+        Ok(())
     }
 
     pub fn get_number_of_sids(&self) -> i32 {
@@ -702,21 +1856,65 @@ impl Player
 
     fn should_quit(&mut self) -> bool {
         let abort_type = self.abort_type.load(Ordering::SeqCst);
-        abort_type == ABORT_TO_QUIT || !self.sid_device.as_mut().unwrap().is_connected(self.device_number)
+        abort_type == ABORT_TO_QUIT || self.sid_device.as_mut().is_some_and(|sid_device| !sid_device.is_connected(self.device_number))
+    }
+
+    // writes to registers that are not whitelisted are replaced by dummy writes to keep the timing,
+    // except for the volume of register $18 which is always passed, otherwise the whitelisted registers would be silent
+    fn filter_sid_write(register_whitelist: &[bool; 256], reg: u8, data: u8) -> (u8, u8) {
+        if register_whitelist[reg as usize] {
+            (reg, data)
+        } else if reg & 0x1f == 0x18 {
+            (reg, data & 0x0f)
+        } else {
+            (DUMMY_REG, 0)
+        }
+    }
+
+    // an unrealistic delay would stall the device, so it's limited to a few seconds
+    fn clamp_write_cycles(cycles: u32) -> (u32, bool) {
+        if cycles > MAX_CYCLES_SID_WRITE {
+            (MAX_CYCLES_SID_WRITE, true)
+        } else {
+            (cycles, false)
+        }
     }
 
     fn process_sid_write(&mut self, reg: u8, data: u8) -> DeviceResponse {
-        let cycles_real = self.acid64_lib.get_cycles(self.c64_instance) as u32;
+        let (cycles_real, clamped) = Self::clamp_write_cycles(self.acid64_lib.get_cycles(self.c64_instance) as u32);
+        if clamped {
+            self.delay_clamped.store(true, Ordering::Relaxed);
+        }
         let cycles = self.adjust_cycles(cycles_real);
 
+        let (reg, data) = match self.register_whitelist {
+            Some(register_whitelist) => Self::filter_sid_write(&register_whitelist, reg, data),
+            _ => (reg, data)
+        };
+
         self.total_cycles = cycles_real;
         self.last_sid_write[reg as usize] = data;
 
+        if let Some(register_log) = self.register_log.as_mut() {
+            register_log.write(cycles_real, reg, data);
+        }
+
+        if let Some(register_stats) = self.register_stats.as_mut() {
+            register_stats.process_write(cycles_real, reg, data);
+        }
+
+        if self.digi_detector.process_write(cycles_real, reg) {
+            self.digi_detected.store(true, Ordering::Relaxed);
+        }
+
+        let data = self.mask_volume(reg, data);
+
         self.sid_data_processor.write(cycles, reg, data, cycles_real);
         let cycles_in_fifo = self.sid_data_processor.get_cycles_in_fifo();
         self.sid_device.as_mut().unwrap().set_cycles_in_fifo(self.device_number, cycles_in_fifo);
 
         let device_response = self.write_to_sid(self.device_number, cycles, reg, data);
+        let device_response = self.write_to_mirrored_sids(reg, data, device_response);
         if device_response == DeviceResponse::Busy {
             return device_response;
         }
@@ -724,6 +1922,56 @@ impl Player
         DeviceResponse::Ok
     }
 
+    fn write_to_mirrored_sids(&mut self, reg: u8, data: u8, device_response: DeviceResponse) -> DeviceResponse {
+        if self.mirrored_sids.is_empty() || reg == DUMMY_REG {
+            return device_response;
+        }
+
+        // the mirrors are decided once when the SIDs are configured, so the write path doesn't change them
+        let sid_nr = reg >> 5;
+        let mirrored_regs = self.mirrored_sids.iter()
+            .filter(|(source_sid_nr, _)| *source_sid_nr == sid_nr)
+            .map(|(_, mirror_sid_nr)| (mirror_sid_nr << 5) | (reg & 0x1f))
+            .collect::<Vec<u8>>();
+
+        let mut device_response = device_response;
+        for mirrored_reg in mirrored_regs {
+            if device_response == DeviceResponse::Busy {
+                self.redo_buffer.push_back(SidWrite::new(mirrored_reg, data, 0, 0));
+            } else {
+                self.sid_data_processor.write(0, mirrored_reg, data, 0);
+                device_response = self.write_to_sid(self.device_number, 0, mirrored_reg, data);
+            }
+        }
+        device_response
+    }
+
+    pub fn get_mirrored_sids(&self) -> Vec<(i32, i32, i32)> {
+        self.mirrored_sids.iter()
+            .map(|&(source_sid_nr, mirror_sid_nr)| (source_sid_nr as i32, mirror_sid_nr as i32, self.acid64_lib.get_sid_address(self.c64_instance, source_sid_nr as i32)))
+            .collect()
+    }
+
+    fn detect_mirrored_sids(&mut self, number_of_sids: i32) {
+        self.mirrored_sids.clear();
+
+        if !self.sid_device.as_mut().unwrap().is_feature_supported(self.device_number, DeviceFeature::MultipleSids) {
+            return;
+        }
+
+        let sid_addresses = (0..number_of_sids).map(|sid_nr| self.acid64_lib.get_sid_address(self.c64_instance, sid_nr)).collect::<Vec<i32>>();
+        self.mirrored_sids = Self::find_mirrored_sids(&sid_addresses);
+    }
+
+    // a SID is mirrored when it has the same address as an earlier SID, the writes of the first of these SIDs are mirrored
+    fn find_mirrored_sids(sid_addresses: &[i32]) -> Vec<(u8, u8)> {
+        sid_addresses.iter().enumerate().skip(1)
+            .filter_map(|(sid_nr, &sid_address)| sid_addresses.iter().position(|&address| address == sid_address)
+                .filter(|&source_sid_nr| source_sid_nr != sid_nr)
+                .map(|source_sid_nr| (source_sid_nr as u8, sid_nr as u8)))
+            .collect()
+    }
+
     fn write_to_sid(&mut self, device_number: i32, cycles: u32, reg: u8, data: u8) -> DeviceResponse {
         self.sid_device.as_mut().unwrap().try_write(device_number, cycles, reg, data)
     }
@@ -794,7 +2042,32 @@ impl Player
     }
 
     fn write_last_sid_write(&mut self, reg: u8) {
-        self.write_to_sid(self.device_number, MIN_CYCLE_SID_WRITE, reg, self.last_sid_write[reg as usize]);
+        let data = self.mask_volume(reg, self.last_sid_write[reg as usize]);
+        self.write_to_sid(self.device_number, MIN_CYCLE_SID_WRITE, reg, data);
+    }
+
+    fn write_last_sid_write_direct(&mut self, reg: u8) {
+        let data = self.mask_volume(reg, self.last_sid_write[reg as usize]);
+        self.write_to_sid_direct(self.device_number, MIN_CYCLE_SID_WRITE, reg, data);
+    }
+
+    fn write_volume_registers(&mut self) {
+        let number_of_sids = self.acid64_lib.get_number_of_sids(self.c64_instance);
+
+        for sid_nr in 0..number_of_sids {
+            self.write_last_sid_write((sid_nr * 0x20) as u8 + 0x18);
+        }
+        self.sid_device.as_mut().unwrap().force_flush(self.device_number);
+    }
+
+    fn mask_volume(&self, reg: u8, data: u8) -> u8 {
+        if reg & 0x1f != 0x18 {
+            data
+        } else if self.muted {
+            data & 0xf0
+        } else {
+            (data & 0xf0) | ((data & 0x0f) * self.volume / MAX_VOLUME)
+        }
     }
 
     fn adjust_cycles(&mut self, cycles: u32) -> u32 {
@@ -829,38 +2102,138 @@ impl Player
         self.fix_device_numbers(number_of_sids)?;
 
         self.sid_device.as_mut().unwrap().set_sid_count(self.device_number, number_of_sids);
-        self.sid_device.as_mut().unwrap().set_sid_position(self.device_number, 50);
+        let sid_position = self.get_sid_position();
+        self.sid_device.as_mut().unwrap().set_sid_position(self.device_number, sid_position);
+
+        let sid_configuration = SidConfiguration {
+            device_numbers: self.device_numbers.clone(),
+            sid_models: self.get_sid_models(number_of_sids),
+            sid_clock: self.sid_clock.unwrap_or(self.get_default_sid_clock())
+        };
 
-        self.configure_sid_model(number_of_sids);
-        self.configure_sid_clock();
+        // changing the SID model and clock takes time on some devices, so it's skipped when the previous file used the same configuration
+        if Self::is_sid_reconfiguration_needed(self.sid_configuration.as_ref(), &sid_configuration) {
+            self.set_sid_models(&sid_configuration.sid_models);
+            self.sid_device.as_mut().unwrap().set_sid_clock(self.device_number, sid_configuration.sid_clock);
+        }
+        self.set_processor_clock(self.get_default_sid_clock());
+        self.sid_configuration = Some(sid_configuration);
+
+        self.detect_mirrored_sids(number_of_sids);
+
+        self.sid_device.as_mut().unwrap().set_sampling_method(self.device_number, self.sampling_method.unwrap_or(SamplingMethod::Best));
+
+        if let Some(sid_header) = self.sid_header.clone() {
+            self.sid_device.as_mut().unwrap().set_sid_header(self.device_number, sid_header);
+        }
+        self.sids_configured = false;
 
-        self.sid_device.as_mut().unwrap().set_sampling_method(self.device_number, SamplingMethod::Best);
         if should_reset {
             self.sid_device.as_mut().unwrap().reset_all_sids(self.device_number);
         }
         Ok(())
     }
 
-    pub fn get_next_song(&self) -> i32 {
-        let number_of_songs = self.get_number_of_songs();
+    fn configure_sid_device_for_register_log(&mut self, header: &RegisterLogHeader) -> Result<(), String> {
+        let default_device_number = i32::from(header.sid_models.first() == Some(&SID_MODEL_8580));
 
-        if self.song_number == number_of_songs - 1 {
-            0
-        } else {
-            self.song_number + 1
+        for device_number in self.device_numbers.iter_mut().filter(|device_number| **device_number == -1) {
+            *device_number = default_device_number;
+        }
+
+        if self.device_number == -1 {
+            self.device_number = default_device_number;
+        }
+
+        self.fix_device_numbers(header.number_of_sids)?;
+
+        self.sid_device.as_mut().unwrap().set_sid_count(self.device_number, header.number_of_sids);
+        let sid_position = self.get_sid_position();
+        self.sid_device.as_mut().unwrap().set_sid_position(self.device_number, sid_position);
+
+        let sid_models = (0..header.number_of_sids as usize).map(|sid_nr| *header.sid_models.get(sid_nr).unwrap_or(&0)).collect::<Vec<i32>>();
+        self.set_sid_models(&sid_models);
+        self.apply_sid_clock(if header.ntsc { SidClock::Ntsc } else { SidClock::Pal });
+        self.sid_configuration = None;
+
+        self.sid_device.as_mut().unwrap().set_sampling_method(self.device_number, self.sampling_method.unwrap_or(SamplingMethod::Best));
+        self.sid_device.as_mut().unwrap().reset_all_sids(self.device_number);
+        Ok(())
+    }
+
+    pub fn set_song_range(&mut self, first_song: i32, last_song: i32) -> Result<(), String> {
+        let number_of_songs = self.get_number_of_songs();
+        if last_song >= number_of_songs {
+            return Err(format!("Songs must be within 1-{number_of_songs}."));
         }
+
+        self.song_range = Some((first_song, last_song));
+        Ok(())
+    }
+
+    pub fn get_song_range(&self) -> (i32, i32) {
+        self.song_range.unwrap_or((0, self.get_number_of_songs() - 1))
+    }
+
+    pub fn get_next_song(&self) -> i32 {
+        song_number::get_next_song_in_range(self.song_number, self.get_song_range())
     }
 
     pub fn get_prev_song(&mut self) -> i32 {
-        if self.song_number == 0 {
-            self.get_number_of_songs() - 1
+        song_number::get_prev_song_in_range(self.song_number, self.get_song_range())
+    }
+
+    pub fn set_song_to_play(&mut self, song_number: i32) -> Result<(), String> {
+        let song_number = self.resolve_song_number(song_number)?;
+
+        if self.seek_time.take().is_some() {
+            self.acid64_lib.stop_seek(self.c64_instance);
+        }
+
+        self.redo_buffer.clear();
+        self.sid_data_processor.init(0);
+        self.sid_device.as_mut().unwrap().set_cycles_in_fifo(self.device_number, 0);
+        self.sid_device.as_mut().unwrap().reset_all_buffers(self.device_number);
+
+        if self.fast_switch && self.sids_configured {
+            self.sid_device.as_mut().unwrap().reset_active_sids(self.device_number);
         } else {
-            self.song_number - 1
+            self.sid_device.as_mut().unwrap().reset_all_sids(self.device_number);
+            self.sids_configured = true;
         }
+
+        if let Some(fade_in_millis) = self.fade_in_millis {
+            self.sid_device.as_mut().unwrap().set_fade_in(self.device_number, fade_in_millis);
+        }
+
+        self.song_number = song_number;
+        self.init_song(song_number);
+
+        if self.estimate_length && self.get_known_song_length(song_number).is_none() && !self.estimated_song_lengths.contains_key(&song_number) {
+            let song_length = self.estimate_song_length();
+
+            // an interrupted estimate is not cached, so it's estimated again when the song is played next time
+            if !self.should_quit() {
+                self.estimated_song_lengths.insert(song_number, song_length);
+            }
+        }
+
+        if let Some(register_log) = self.register_log.as_mut() {
+            register_log.song(song_number);
+        }
+        Ok(())
     }
 
-    pub fn set_song_to_play(&mut self, song_number: i32) -> Result<(), String> {
-        let song_number = if song_number == -1 {
+    pub fn set_song_to_analyze(&mut self, song_number: i32) -> Result<(), String> {
+        let song_number = self.resolve_song_number(song_number)?;
+
+        self.song_number = song_number;
+        self.init_song(song_number);
+        Ok(())
+    }
+
+    fn resolve_song_number(&self, song_number: i32) -> Result<i32, String> {
+        let song_number = if song_number == DEFAULT_SONG {
             self.acid64_lib.get_default_song(self.c64_instance)
         } else {
             song_number
@@ -869,46 +2242,136 @@ impl Player
         let number_of_songs = self.acid64_lib.get_number_of_songs(self.c64_instance);
 
         if song_number < 0 || song_number >= number_of_songs {
-            return Err(format!("Song number {} doesn't exist.", song_number + 1));
+            return Err(format!("Song number {} doesn't exist.", song_number::to_user_song_number(song_number)));
         }
+        Ok(song_number)
+    }
 
-        self.redo_buffer.clear();
-        self.sid_data_processor.init(0);
-        self.sid_device.as_mut().unwrap().set_cycles_in_fifo(self.device_number, 0);
-        self.sid_device.as_mut().unwrap().reset_all_buffers(self.device_number);
-        self.sid_device.as_mut().unwrap().reset_all_sids(self.device_number);
-
-        self.song_number = song_number;
-
+    fn init_song(&mut self, song_number: i32) {
         self.acid64_lib.set_song_to_play(self.c64_instance, song_number);
 
+        if let Some(c64_version) = self.c64_version {
+            self.acid64_lib.set_c64_version(self.c64_instance, c64_version);
+        }
+
         self.acid64_lib.skip_silence(self.c64_instance, true);
-        self.acid64_lib.enable_volume_fix(self.c64_instance, true);
+        self.acid64_lib.enable_volume_fix(self.c64_instance, self.volume_fix);
+    }
 
-        Ok(())
+    // the scan emulates a few seconds of the song, so the result is cached per sub tune
+    pub fn scan_digi_type(&mut self) -> Option<String> {
+        if let Some((song_number, digi_type)) = &self.scanned_digi_type {
+            if *song_number == self.song_number {
+                return digi_type.clone();
+            }
+        }
+
+        let digi_type = self.detect_digi_type();
+        self.scanned_digi_type = Some((self.song_number, digi_type.clone()));
+        digi_type
     }
 
-    pub fn configure_sid_model(&mut self, number_of_sids: i32) {
-        for i in 0..number_of_sids {
-            let device_number = self.device_numbers.get(i as usize).unwrap_or(&0);
-            let sid_model = self.acid64_lib.get_sid_model(self.c64_instance, i);
+    fn detect_digi_type(&mut self) -> Option<String> {
+        let cycles_per_second = self.get_tune_cycles_per_second();
+        let cycles_to_run = DIGI_SCAN_SECONDS * cycles_per_second as u64;
+
+        let mut digi_detector = DigiDetector::new();
+        let mut pending_cycles: u32 = 0;
+
+        self.analyze_silently(cycles_to_run, cycles_per_second, DIGI_SCAN_TIMEOUT_MILLIS, &mut |event| match event {
+            SilentRunEvent::Cycles(cycles) => {
+                pending_cycles += cycles;
+                false
+            },
+            SilentRunEvent::Write(cycles, reg, _) => {
+                let detected = digi_detector.process_write(pending_cycles + cycles, reg);
+                pending_cycles = 0;
+                detected
+            }
+        });
+
+        digi_detector.get_digi_type().map(|digi_type| digi_type.to_string())
+    }
+
+    // emulates the song silently until it becomes silent or loops, limited in emulated and real time
+    fn estimate_song_length(&mut self) -> Option<i32> {
+        let cycles_per_second = self.get_tune_cycles_per_second();
+        let max_cycles = ESTIMATE_MAX_SECONDS * cycles_per_second as u64;
+        let silence_cycles = ESTIMATE_SILENCE_SECONDS * cycles_per_second as u64;
+
+        let mut loop_detector = LoopDetector::new(self.get_cycles_per_frame(), cycles_per_second);
+        let mut sid_registers = [0u8; 256];
+        let mut cycles: u64 = 0;
+        let mut last_change_cycles: Option<u64> = None;
+        let mut song_length = None;
+
+        self.analyze_silently(max_cycles, cycles_per_second, ESTIMATE_TIMEOUT_MILLIS, &mut |event| {
+            let command_cycles = match event {
+                SilentRunEvent::Cycles(command_cycles) => command_cycles,
+                SilentRunEvent::Write(command_cycles, reg, data) => {
+                    if sid_registers[reg as usize] != data {
+                        sid_registers[reg as usize] = data;
+                        last_change_cycles = Some(cycles + command_cycles as u64);
+                    }
+                    command_cycles
+                }
+            };
+
+            cycles += command_cycles as u64;
+
+            if let Some(detected_loop) = loop_detector.add_cycles(command_cycles, &sid_registers) {
+                song_length = Some((detected_loop.start_in_millis + detected_loop.duration_in_millis) as i32);
+                return true;
+            }
+
+            if let Some(last_change_cycles) = last_change_cycles {
+                if cycles - last_change_cycles >= silence_cycles {
+                    song_length = Some((last_change_cycles * 1000 / cycles_per_second as u64) as i32);
+                    return true;
+                }
+            }
+            false
+        });
+
+        song_length
+    }
+
+    fn get_sid_models(&self, number_of_sids: i32) -> Vec<i32> {
+        (0..number_of_sids).map(|sid_nr| self.acid64_lib.get_sid_model(self.c64_instance, sid_nr)).collect()
+    }
+
+    fn is_sid_reconfiguration_needed(last_sid_configuration: Option<&SidConfiguration>, sid_configuration: &SidConfiguration) -> bool {
+        last_sid_configuration != Some(sid_configuration)
+    }
+
+    fn set_sid_models(&mut self, sid_models: &[i32]) {
+        for (i, sid_model) in sid_models.iter().enumerate() {
+            let device_number = self.device_numbers.get(i).unwrap_or(&0);
             match sid_model {
-                2 => self.sid_device.as_mut().unwrap().set_sid_model(*device_number, i, SidModel::Mos8580),
-                _ => self.sid_device.as_mut().unwrap().set_sid_model(*device_number, i, SidModel::Mos6581)
+                2 => self.sid_device.as_mut().unwrap().set_sid_model(*device_number, i as i32, SidModel::Mos8580),
+                _ => self.sid_device.as_mut().unwrap().set_sid_model(*device_number, i as i32, SidModel::Mos6581)
             }
         }
     }
 
-    pub fn configure_sid_clock(&mut self) {
-        let c64_model = self.acid64_lib.get_c64_version(self.c64_instance);
-
-        match c64_model {
-            2 => self.sid_device.as_mut().unwrap().set_sid_clock(self.device_number, SidClock::Ntsc),
-            _ => self.sid_device.as_mut().unwrap().set_sid_clock(self.device_number, SidClock::Pal)
+    fn get_default_sid_clock(&self) -> SidClock {
+        match self.get_c64_version() {
+            2 => SidClock::Ntsc,
+            _ => SidClock::Pal
         }
+    }
+
+    fn apply_sid_clock(&mut self, default_sid_clock: SidClock) {
+        let sid_clock = self.sid_clock.unwrap_or(default_sid_clock);
+        self.sid_device.as_mut().unwrap().set_sid_clock(self.device_number, sid_clock);
+        self.set_processor_clock(default_sid_clock);
+    }
 
+    // the device clock paces the writes, while the time is based on the clock of the tune to match the song length
+    fn set_processor_clock(&mut self, tune_clock: SidClock) {
         let device_clock = self.sid_device.as_mut().unwrap().get_device_clock(self.device_number);
         self.sid_data_processor.set_sid_clock(device_clock);
+        self.sid_data_processor.set_display_clock(tune_clock);
     }
 
     fn get_valid_device_number(&mut self, device_number: i32) -> i32 {
@@ -961,3 +2424,139 @@ impl Player
         Ok(())
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn frames_advance_pal_cycles() {
+        assert_eq!(Player::frames_to_cycles(1, 1), 312 * 63);
+        assert_eq!(Player::frames_to_cycles(50, 1), PAL_CYCLES_PER_SECOND as u64);
+    }
+
+    #[test]
+    fn frames_advance_ntsc_cycles() {
+        assert_eq!(Player::frames_to_cycles(1, 2), 263 * 65);
+        assert_eq!(Player::frames_to_cycles(60, 2), NTSC_CYCLES_PER_SECOND as u64);
+    }
+
+    #[test]
+    fn zero_frames_advance_no_cycles() {
+        assert_eq!(Player::frames_to_cycles(0, 1), 0);
+    }
+
+    #[test]
+    fn passes_whitelisted_writes() {
+        let register_whitelist = Player::create_register_whitelist(&[0x15, 0x16, 0x17, 0x18]);
+        assert_eq!(Player::filter_sid_write(&register_whitelist, 0x17, 0xf1), (0x17, 0xf1));
+        assert_eq!(Player::filter_sid_write(&register_whitelist, 0x18, 0x1f), (0x18, 0x1f));
+    }
+
+    #[test]
+    fn replaces_other_writes_by_dummy_writes() {
+        let register_whitelist = Player::create_register_whitelist(&[0x00, 0x01]);
+        assert_eq!(Player::filter_sid_write(&register_whitelist, 0x04, 0x41), (DUMMY_REG, 0));
+        assert_eq!(Player::filter_sid_write(&register_whitelist, 0x20, 0x12), (DUMMY_REG, 0));
+    }
+
+    #[test]
+    fn always_passes_the_volume_of_each_sid() {
+        let register_whitelist = Player::create_register_whitelist(&[0x00, 0x01]);
+        assert_eq!(Player::filter_sid_write(&register_whitelist, 0x18, 0x1f), (0x18, 0x0f));
+        assert_eq!(Player::filter_sid_write(&register_whitelist, 0x38, 0x7a), (0x38, 0x0a));
+        assert_eq!(Player::filter_sid_write(&register_whitelist, 0x58, 0x00), (0x58, 0x00));
+    }
+
+    #[test]
+    fn keeps_realistic_write_cycles() {
+        assert_eq!(Player::clamp_write_cycles(0), (0, false));
+        assert_eq!(Player::clamp_write_cycles(PAL_CYCLES_PER_SECOND), (PAL_CYCLES_PER_SECOND, false));
+        assert_eq!(Player::clamp_write_cycles(MAX_CYCLES_SID_WRITE), (MAX_CYCLES_SID_WRITE, false));
+    }
+
+    #[test]
+    fn clamps_huge_write_cycles() {
+        assert_eq!(Player::clamp_write_cycles(MAX_CYCLES_SID_WRITE + 1), (MAX_CYCLES_SID_WRITE, true));
+        assert_eq!(Player::clamp_write_cycles(u32::MAX), (MAX_CYCLES_SID_WRITE, true));
+    }
+
+    #[test]
+    fn converts_int_to_bcd() {
+        assert_eq!(Player::int_to_bcd(0), 0x00);
+        assert_eq!(Player::int_to_bcd(9), 0x09);
+        assert_eq!(Player::int_to_bcd(10), 0x10);
+        assert_eq!(Player::int_to_bcd(59), 0x59);
+        assert_eq!(Player::int_to_bcd(99), 0x99);
+    }
+
+    #[test]
+    fn converts_song_lengths_to_bcd() {
+        assert_eq!(Player::convert_song_lengths_to_bcd(&[0]), vec![0x00, 0x00]);
+        assert_eq!(Player::convert_song_lengths_to_bcd(&[59_000]), vec![0x00, 0x59]);
+        assert_eq!(Player::convert_song_lengths_to_bcd(&[60_000]), vec![0x01, 0x00]);
+        assert_eq!(Player::convert_song_lengths_to_bcd(&[(99 * 60 + 59) * 1000]), vec![0x99, 0x59]);
+        assert_eq!(Player::convert_song_lengths_to_bcd(&[59_000, 60_000]), vec![0x00, 0x59, 0x01, 0x00]);
+    }
+
+    #[test]
+    fn rounds_song_lengths_to_seconds() {
+        assert_eq!(Player::convert_song_lengths_to_bcd(&[59_499]), vec![0x00, 0x59]);
+        assert_eq!(Player::convert_song_lengths_to_bcd(&[59_500]), vec![0x01, 0x00]);
+    }
+
+    #[test]
+    fn caps_song_lengths_of_100_minutes_and_longer() {
+        assert_eq!(Player::convert_song_lengths_to_bcd(&[100 * 60 * 1000]), vec![0x99, 0x59]);
+    }
+
+    #[test]
+    fn steps_a_full_frame_without_remainder() {
+        let cycles_per_frame = Player::cycles_per_frame(1) as u64;
+        assert_eq!(Player::get_cycles_to_next_frame(cycles_per_frame, 0), cycles_per_frame);
+    }
+
+    #[test]
+    fn steps_to_the_next_frame_boundary_with_remainder() {
+        let cycles_per_frame = Player::cycles_per_frame(2) as u64;
+        assert_eq!(Player::get_cycles_to_next_frame(cycles_per_frame, 100), cycles_per_frame - 100);
+        assert_eq!(Player::get_cycles_to_next_frame(cycles_per_frame, cycles_per_frame + 100), cycles_per_frame - 100);
+    }
+
+    fn create_sid_configuration(sid_models: Vec<i32>, sid_clock: SidClock) -> SidConfiguration {
+        SidConfiguration { device_numbers: vec![0; sid_models.len()], sid_models, sid_clock }
+    }
+
+    #[test]
+    fn configures_sids_of_first_file() {
+        let sid_configuration = create_sid_configuration(vec![1], SidClock::Pal);
+        assert!(Player::is_sid_reconfiguration_needed(None, &sid_configuration));
+    }
+
+    #[test]
+    fn skips_configuration_of_uniform_files() {
+        let last_sid_configuration = create_sid_configuration(vec![1, 1], SidClock::Pal);
+        let sid_configuration = create_sid_configuration(vec![1, 1], SidClock::Pal);
+        assert!(!Player::is_sid_reconfiguration_needed(Some(&last_sid_configuration), &sid_configuration));
+    }
+
+    #[test]
+    fn configures_sids_of_mixed_files() {
+        let last_sid_configuration = create_sid_configuration(vec![1, 1], SidClock::Pal);
+        assert!(Player::is_sid_reconfiguration_needed(Some(&last_sid_configuration), &create_sid_configuration(vec![1, 2], SidClock::Pal)));
+        assert!(Player::is_sid_reconfiguration_needed(Some(&last_sid_configuration), &create_sid_configuration(vec![1, 1], SidClock::Ntsc)));
+        assert!(Player::is_sid_reconfiguration_needed(Some(&last_sid_configuration), &create_sid_configuration(vec![1], SidClock::Pal)));
+    }
+
+    #[test]
+    fn finds_no_mirrored_sids_at_different_addresses() {
+        assert!(Player::find_mirrored_sids(&[0xd400, 0xd420, 0xd440]).is_empty());
+        assert!(Player::find_mirrored_sids(&[0xd400, 0xde00]).is_empty());
+    }
+
+    #[test]
+    fn finds_mirrored_sids() {
+        assert_eq!(Player::find_mirrored_sids(&[0xd400, 0xd400]), vec![(0, 1)]);
+        assert_eq!(Player::find_mirrored_sids(&[0xd400, 0xde00, 0xde00]), vec![(1, 2)]);
+    }
+}
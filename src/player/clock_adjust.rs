@@ -8,12 +8,15 @@ use super::MIN_CYCLE_SID_WRITE;
 const ONE_MH_CLOCK: f64 = 1_000_000.0;
 const PAL_CLOCK: f64 = 17_734_475.0 / 18.0;
 const NTSC_CLOCK: f64 = 14_318_180.0 / 14.0;
+const DREAN_CLOCK: f64 = 14_328_225.0 / 14.0;
 
 const PAL_CLOCK_SCALE: f64 = (ONE_MH_CLOCK - PAL_CLOCK) / ONE_MH_CLOCK;
 const NTSC_CLOCK_SCALE: f64 = (NTSC_CLOCK - ONE_MH_CLOCK) / ONE_MH_CLOCK;
+const DREAN_CLOCK_SCALE: f64 = (DREAN_CLOCK - ONE_MH_CLOCK) / ONE_MH_CLOCK;
 
-const PAL_FREQ_SCALE: u32 = (((PAL_CLOCK - ONE_MH_CLOCK) * 65_536.0 / PAL_CLOCK) + 65_536.0) as u32;
-const NTSC_FREQ_SCALE: u32 = (((NTSC_CLOCK - ONE_MH_CLOCK) * 65_536.0 / NTSC_CLOCK) + 65_536.0) as u32;
+const PAL_FREQ_SCALE: u64 = (((PAL_CLOCK - ONE_MH_CLOCK) * 65_536.0 / PAL_CLOCK) + 65_536.0) as u64;
+const NTSC_FREQ_SCALE: u64 = (((NTSC_CLOCK - ONE_MH_CLOCK) * 65_536.0 / NTSC_CLOCK) + 65_536.0) as u64;
+const DREAN_FREQ_SCALE: u64 = (((DREAN_CLOCK - ONE_MH_CLOCK) * 65_536.0 / DREAN_CLOCK) + 65_536.0) as u64;
 
 pub struct ClockAdjust {
     total_cycles_to_stretch: f64,
@@ -51,7 +54,8 @@ impl ClockAdjust {
                 cycles += stretch_rounded;
             }
         } else {
-            self.total_cycles_to_stretch += cycles * NTSC_CLOCK_SCALE;
+            let clock_scale = if self.clock == SidClock::Drean { DREAN_CLOCK_SCALE } else { NTSC_CLOCK_SCALE };
+            self.total_cycles_to_stretch += cycles * clock_scale;
 
             if self.total_cycles_to_stretch >= 1.0 {
                 if cycles > self.total_cycles_to_stretch {
@@ -82,12 +86,14 @@ impl ClockAdjust {
     }
 
     pub fn scale_frequency(&mut self, voice_index: u8) -> u32 {
-        let freq = self.freq[voice_index as usize];
+        // the multiplication is done in 64 bits, since a high frequency times the NTSC scale doesn't fit in 32 bits
+        let freq = self.freq[voice_index as usize] as u64;
 
         let scaled_freq = match self.clock {
-            SidClock::Ntsc => min((freq * NTSC_FREQ_SCALE) >> 16, 0xffff),
+            SidClock::Ntsc | SidClock::NtscOld => min((freq * NTSC_FREQ_SCALE) >> 16, 0xffff),
+            SidClock::Drean => min((freq * DREAN_FREQ_SCALE) >> 16, 0xffff),
             _ => (freq * PAL_FREQ_SCALE) >> 16
-        };
+        } as u32;
 
         self.last_freq[voice_index as usize] = scaled_freq;
         scaled_freq
@@ -101,3 +107,103 @@ impl ClockAdjust {
         };
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const DEVICE_CLOCK: f64 = ONE_MH_CLOCK;
+
+    fn adjust_one_second(clock: SidClock, tune_clock: f64, cycles_per_write: u32) -> u64 {
+        let mut clock_adjust = ClockAdjust::new();
+        clock_adjust.init(clock);
+
+        let writes = tune_clock as u32 / cycles_per_write;
+        (0..writes).map(|_| clock_adjust.adjust_cycles(cycles_per_write) as u64).sum()
+    }
+
+    fn assert_near(actual: f64, expected: f64, tolerance: f64) {
+        assert!((actual - expected).abs() <= expected * tolerance, "{actual} is not within {tolerance} of {expected}");
+    }
+
+    fn scale_frequency(clock: SidClock, freq: u16) -> u32 {
+        let mut clock_adjust = ClockAdjust::new();
+        clock_adjust.init(clock);
+        clock_adjust.update_frequency(0, 0, (freq & 0xff) as u8);
+        clock_adjust.update_frequency(0, 1, (freq >> 8) as u8);
+        clock_adjust.scale_frequency(0)
+    }
+
+    #[test]
+    fn stretches_pal_cycles_to_device_clock() {
+        let device_cycles = adjust_one_second(SidClock::Pal, PAL_CLOCK, 100);
+        assert_near(device_cycles as f64, DEVICE_CLOCK, 0.001);
+    }
+
+    #[test]
+    fn shrinks_ntsc_cycles_to_device_clock() {
+        let device_cycles = adjust_one_second(SidClock::Ntsc, NTSC_CLOCK, 100);
+        assert_near(device_cycles as f64, DEVICE_CLOCK, 0.001);
+    }
+
+    #[test]
+    fn shrinks_drean_cycles_to_device_clock() {
+        let device_cycles = adjust_one_second(SidClock::Drean, DREAN_CLOCK, 100);
+        assert_near(device_cycles as f64, DEVICE_CLOCK, 0.001);
+    }
+
+    #[test]
+    fn keeps_the_minimum_cycles_between_writes() {
+        let mut clock_adjust = ClockAdjust::new();
+        clock_adjust.init(SidClock::Ntsc);
+        assert_eq!(clock_adjust.adjust_cycles(1), MIN_CYCLE_SID_WRITE);
+    }
+
+    #[test]
+    fn scales_pal_frequency_to_device_clock() {
+        let scaled_freq = scale_frequency(SidClock::Pal, 0x1000);
+        assert_near(scaled_freq as f64, 0x1000 as f64 * PAL_CLOCK / DEVICE_CLOCK, 0.001);
+    }
+
+    #[test]
+    fn scales_ntsc_frequency_to_device_clock() {
+        let scaled_freq = scale_frequency(SidClock::Ntsc, 0x1000);
+        assert_near(scaled_freq as f64, 0x1000 as f64 * NTSC_CLOCK / DEVICE_CLOCK, 0.001);
+    }
+
+    #[test]
+    fn caps_scaled_ntsc_frequency() {
+        assert_eq!(scale_frequency(SidClock::Ntsc, 0xffff), 0xffff);
+        assert_eq!(scale_frequency(SidClock::Drean, 0xffff), 0xffff);
+    }
+
+    #[test]
+    fn updates_frequency_bytes_separately() {
+        let mut clock_adjust = ClockAdjust::new();
+        clock_adjust.init(SidClock::Pal);
+        clock_adjust.update_frequency(1, 1, 0x12);
+        clock_adjust.update_frequency(1, 0, 0x34);
+        assert_eq!(clock_adjust.freq[1], 0x1234);
+
+        clock_adjust.update_frequency(1, 1, 0x56);
+        assert_eq!(clock_adjust.freq[1], 0x5634);
+    }
+
+    #[test]
+    fn detects_a_change_of_the_scaled_high_byte() {
+        let mut clock_adjust = ClockAdjust::new();
+        clock_adjust.init(SidClock::Ntsc);
+        clock_adjust.update_frequency(0, 1, 0x10);
+        clock_adjust.scale_frequency(0);
+
+        let last_freq = clock_adjust.get_last_scaled_freq(0);
+        clock_adjust.update_frequency(0, 0, 0x01);
+        let scaled_freq = clock_adjust.scale_frequency(0);
+        assert_eq!(last_freq & 0xff00, scaled_freq & 0xff00);
+
+        let last_freq = clock_adjust.get_last_scaled_freq(0);
+        clock_adjust.update_frequency(0, 1, 0x20);
+        let scaled_freq = clock_adjust.scale_frequency(0);
+        assert_ne!(last_freq & 0xff00, scaled_freq & 0xff00);
+    }
+}
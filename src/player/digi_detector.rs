@@ -0,0 +1,60 @@
+// Copyright (C) 2024 Wilfred Bos
+// Licensed under the GNU GPL v3 license. See the LICENSE file for the terms and conditions.
+
+const CYCLES_PER_FRAME: u32 = 312 * 63;
+const MIN_VOLUME_WRITES_PER_FRAME: u32 = 16;
+const MIN_DIGI_FRAMES: u32 = 10;
+
+pub struct DigiDetector {
+    cycles_in_frame: u32,
+    volume_writes: u32,
+    digi_frames: u32,
+    detected: bool
+}
+
+impl DigiDetector {
+    pub fn new() -> DigiDetector {
+        DigiDetector {
+            cycles_in_frame: 0,
+            volume_writes: 0,
+            digi_frames: 0,
+            detected: false
+        }
+    }
+
+    pub fn init(&mut self) {
+        self.cycles_in_frame = 0;
+        self.volume_writes = 0;
+        self.digi_frames = 0;
+        self.detected = false;
+    }
+
+    pub fn process_write(&mut self, cycles: u32, reg: u8) -> bool {
+        if self.detected {
+            return true;
+        }
+
+        self.cycles_in_frame += cycles;
+        while self.cycles_in_frame >= CYCLES_PER_FRAME {
+            self.cycles_in_frame -= CYCLES_PER_FRAME;
+
+            if self.volume_writes >= MIN_VOLUME_WRITES_PER_FRAME {
+                self.digi_frames += 1;
+            } else {
+                self.digi_frames = 0;
+            }
+            self.volume_writes = 0;
+        }
+
+        if reg & 0x1f == 0x18 {
+            self.volume_writes += 1;
+        }
+
+        self.detected = self.digi_frames >= MIN_DIGI_FRAMES;
+        self.detected
+    }
+
+    pub fn get_digi_type(&self) -> Option<&'static str> {
+        self.detected.then_some("volume")
+    }
+}
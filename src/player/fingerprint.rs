@@ -0,0 +1,91 @@
+// Copyright (C) 2024 Wilfred Bos
+// Licensed under the GNU GPL v3 license. See the LICENSE file for the terms and conditions.
+
+use std::fmt::Write as _;
+use std::fs;
+use super::sid_device::DUMMY_REG;
+
+const SID_REGISTER_COUNT: u8 = 0x20;
+const WRITABLE_REGISTER_COUNT: u8 = 0x19;
+const CELL_WIDTH: u32 = 4;
+const CELL_HEIGHT: u32 = 6;
+const SID_SPACING: u32 = 4;
+const BACKGROUND_COLOR: &str = "#101820";
+const CELL_COLOR: &str = "#40c0ff";
+
+// counts the writes per SID register for every second of the song and renders them as a heatmap,
+// with a column per second and a row per register
+pub struct Fingerprint {
+    cycles_per_second: u32,
+    cycles_in_second: u32,
+    number_of_sids: usize,
+    register_writes: Vec<Vec<u32>>
+}
+
+impl Fingerprint {
+    pub fn new(cycles_per_second: u32, number_of_sids: i32) -> Fingerprint {
+        let number_of_sids = number_of_sids.max(1) as usize;
+
+        Fingerprint {
+            cycles_per_second,
+            cycles_in_second: 0,
+            number_of_sids,
+            register_writes: vec![vec![0; number_of_sids * WRITABLE_REGISTER_COUNT as usize]]
+        }
+    }
+
+    pub fn add_cycles(&mut self, cycles: u32) {
+        self.cycles_in_second += cycles;
+
+        while self.cycles_in_second >= self.cycles_per_second {
+            self.cycles_in_second -= self.cycles_per_second;
+            self.register_writes.push(vec![0; self.number_of_sids * WRITABLE_REGISTER_COUNT as usize]);
+        }
+    }
+
+    pub fn process_write(&mut self, cycles: u32, reg: u8) {
+        self.add_cycles(cycles);
+
+        let sid_nr = (reg / SID_REGISTER_COUNT) as usize;
+        let sid_reg = reg % SID_REGISTER_COUNT;
+
+        if reg == DUMMY_REG || sid_reg >= WRITABLE_REGISTER_COUNT || sid_nr >= self.number_of_sids {
+            return;
+        }
+
+        if let Some(writes) = self.register_writes.last_mut() {
+            writes[sid_nr * WRITABLE_REGISTER_COUNT as usize + sid_reg as usize] += 1;
+        }
+    }
+
+    pub fn write_svg(&self, filename: &str) -> Result<(), String> {
+        fs::write(filename, self.get_svg()).map_err(|error| format!("Fingerprint '{filename}' could not be written: {error}"))
+    }
+
+    fn get_svg(&self) -> String {
+        let seconds = self.register_writes.len() as u32;
+        let width = seconds * CELL_WIDTH;
+        let height = self.number_of_sids as u32 * (WRITABLE_REGISTER_COUNT as u32 * CELL_HEIGHT + SID_SPACING) - SID_SPACING;
+        let max_writes = self.register_writes.iter().flatten().copied().max().unwrap_or(0).max(1);
+
+        let mut svg = String::new();
+        let _ = writeln!(svg, "<svg xmlns=\"http://www.w3.org/2000/svg\" width=\"{width}\" height=\"{height}\" viewBox=\"0 0 {width} {height}\">");
+        let _ = writeln!(svg, "<rect width=\"{width}\" height=\"{height}\" fill=\"{BACKGROUND_COLOR}\"/>");
+
+        for (second, writes) in self.register_writes.iter().enumerate() {
+            for (index, &count) in writes.iter().enumerate().filter(|(_, &count)| count > 0) {
+                let sid_nr = index as u32 / WRITABLE_REGISTER_COUNT as u32;
+                let sid_reg = index as u32 % WRITABLE_REGISTER_COUNT as u32;
+
+                // a logarithmic scale keeps registers with few writes visible next to busy registers
+                let opacity = ((count as f64).ln_1p() / (max_writes as f64).ln_1p()).max(0.1);
+                let _ = writeln!(svg, "<rect x=\"{}\" y=\"{}\" width=\"{CELL_WIDTH}\" height=\"{CELL_HEIGHT}\" fill=\"{CELL_COLOR}\" fill-opacity=\"{opacity:.2}\"/>",
+                    second as u32 * CELL_WIDTH,
+                    sid_nr * (WRITABLE_REGISTER_COUNT as u32 * CELL_HEIGHT + SID_SPACING) + sid_reg * CELL_HEIGHT);
+            }
+        }
+
+        svg.push_str("</svg>\n");
+        svg
+    }
+}
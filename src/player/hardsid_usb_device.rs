@@ -2,10 +2,10 @@
 // Licensed under the GNU GPL v3 license. See the LICENSE file for the terms and conditions.
 
 use super::clock_adjust::ClockAdjust;
-use super::hardsid_usb::{HardSidUsb, HSID_USB_STATE_OK, HSID_USB_STATE_ERROR, HSID_USB_STATE_BUSY, DEV_TYPE_HS_4U, DEV_TYPE_HS_UPLAY, DEV_TYPE_HS_UNO};
-use super::sid_device::{DeviceId, DeviceInfo, DeviceResponse, SamplingMethod, SidClock, SidDevice, SidModel};
+use super::hardsid_usb::{HardSidUsb, HsidDevType, HSID_USB_STATE_OK, HSID_USB_STATE_ERROR, HSID_USB_STATE_BUSY, DEV_TYPE_HS_4U, DEV_TYPE_HS_UPLAY, DEV_TYPE_HS_UNO};
+use super::sid_device::{DeviceFeature, DeviceId, DeviceInfo, DeviceResponse, SamplingMethod, SidClock, SidDevice, SidModel};
 use super::{ABORT_NO, ABORTING, MIN_CYCLE_SID_WRITE};
-use crate::utils::{armsid, armsid::SidFilter, fpgasid};
+use crate::utils::{armsid, armsid::SidFilter, error_log, fpgasid};
 
 use std::collections::VecDeque;
 use std::sync::atomic::{Ordering, AtomicI32};
@@ -16,6 +16,7 @@ const ERROR_MSG_DEVICE_FAILURE: &str = "Failure occurred during interaction with
 const ERROR_MSG_INIT_DEVICE: &str = "Initializing HardSID USB device failed with error:";
 const ERROR_MSG_NO_HARDSID_FOUND: &str = "No HardSID USB device found.";
 const ERROR_MSG_DEVICE_COUNT_CHANGED: &str = "Number of devices is changed.";
+const ERROR_MSG_DEVICE_BUSY_TIMEOUT: &str = "Device stayed busy for too long.";
 
 const HS_MIN_CYCLE_SID_WRITE: u32 = 4;
 
@@ -108,6 +109,10 @@ impl SidDevice for HardsidUsbDeviceFacade {
         self.hs_device.reset_all_buffers(dev_nr);
     }
 
+    fn clear_all_buffers(&mut self, dev_nr: i32) {
+        self.hs_device.reset_all_buffers(dev_nr);
+    }
+
     fn enable_turbo_mode(&mut self, _dev_nr: i32) {
         self.hs_device.enable_turbo_mode();
     }
@@ -159,6 +164,14 @@ impl SidDevice for HardsidUsbDeviceFacade {
     fn set_cycles_in_fifo(&mut self, _dev_nr: i32, _cycles: u32) {
         // not supported
     }
+
+    fn is_feature_supported(&mut self, _dev_nr: i32, feature: DeviceFeature) -> bool {
+        feature != DeviceFeature::FadeIn
+    }
+
+    fn get_buffer_fill(&mut self, _dev_nr: i32) -> Option<f32> {
+        None
+    }
 }
 
 pub enum DeviceCommand {
@@ -201,6 +214,9 @@ pub struct HardsidUsbDevice {
     device_model: Vec<SidModel>,
     sid_write_fifo: VecDeque<SidWrite>,
     use_native_device_clock: bool,
+    mono_downmix: bool,
+    busy_timeout_millis: Option<u64>,
+    busy_since: Option<time::Instant>,
     clock_adjust: ClockAdjust,
     cycles_to_compensate: u32,
     device_init_done: Vec<bool>,
@@ -226,6 +242,9 @@ impl HardsidUsbDevice {
             device_model: vec![],
             sid_write_fifo: VecDeque::new(),
             use_native_device_clock: true,
+            mono_downmix: false,
+            busy_timeout_millis: None,
+            busy_since: None,
             clock_adjust: ClockAdjust::new(),
             cycles_to_compensate: 0,
             device_init_done: vec![],
@@ -258,21 +277,9 @@ impl HardsidUsbDevice {
                 for i in 0..dev_count {
                     let dev_type = usb_device.get_device_type(i);
                     let dev_sid_count = usb_device.get_sid_count(i);
-
-                    for j in 0..dev_sid_count {
-                        self.device_type.push(dev_type);
-                        self.device_id.push(i);
-                        self.device_index.push(dev_type_count[dev_type as usize]);
-                        self.device_base_reg.push(j * 0x20);
-                        self.device_mappings.push(j as i32);
-                        self.device_model.push(SidModel::Mos6581);
-                        self.device_init_done.push(false);
-                        dev_type_count[dev_type as usize] += 1;
-                    }
+                    self.add_device(i, dev_type, dev_sid_count, &mut dev_type_count);
                 }
 
-                self.sid_count = self.device_id.len() as i32;
-
                 self.sid_device = Some(usb_device);
                 Ok(())
             } else {
@@ -313,6 +320,7 @@ impl HardsidUsbDevice {
     }
 
     pub fn disconnect_with_error(&mut self, error_message: String) {
+        error_log::write(&error_message);
         self.last_error = Some(error_message);
         self.disconnect();
     }
@@ -340,6 +348,21 @@ impl HardsidUsbDevice {
         }
     }
 
+    // each SID of a physical device gets its own device number, the index counts the physical devices per type
+    fn add_device(&mut self, dev_id: u8, dev_type: HsidDevType, dev_sid_count: u8, dev_type_count: &mut [u8; 4]) {
+        for j in 0..dev_sid_count {
+            self.device_type.push(dev_type);
+            self.device_id.push(dev_id);
+            self.device_index.push(dev_type_count[dev_type as usize]);
+            self.device_base_reg.push(j * 0x20);
+            self.device_mappings.push(j as i32);
+            self.device_model.push(SidModel::Mos6581);
+            self.device_init_done.push(false);
+        }
+        dev_type_count[dev_type as usize] += 1;
+        self.sid_count = self.device_id.len() as i32;
+    }
+
     pub fn can_pair_devices(&mut self, dev1: i32, dev2: i32) -> bool {
         dev1 != dev2 &&
             self.device_id[dev1 as usize] == self.device_id[dev2 as usize] &&
@@ -352,14 +375,17 @@ impl HardsidUsbDevice {
 
     pub fn get_device_info(&self, dev_nr: i32) -> DeviceInfo {
         let dev_name = match self.device_type[dev_nr as usize] {
-            DEV_TYPE_HS_4U => "HardSID 4U ",
-            DEV_TYPE_HS_UPLAY => "HS UPlay ",
-            DEV_TYPE_HS_UNO => "HardSID Uno ",
-            _ => "Unknown HS "
+            DEV_TYPE_HS_4U => "HardSID 4U",
+            DEV_TYPE_HS_UPLAY => "HS UPlay",
+            DEV_TYPE_HS_UNO => "HardSID Uno",
+            _ => "Unknown HS"
         };
         let dev_index = self.device_index[dev_nr as usize];
 
-        let device_name = dev_name.to_string() + &(dev_index + 1).to_string();
+        let mut device_name = format!("{dev_name} #{}", dev_index + 1);
+        if self.device_type[dev_nr as usize] == DEV_TYPE_HS_4U {
+            device_name += &format!(" socket {}", self.device_base_reg[dev_nr as usize] / 0x20 + 1);
+        }
         DeviceInfo { id: device_name.clone(), name: device_name }
     }
 
@@ -636,6 +662,14 @@ impl HardsidUsbDevice {
         self.use_native_device_clock = enabled;
     }
 
+    pub fn set_mono_downmix(&mut self, mono_downmix: bool) {
+        self.mono_downmix = mono_downmix;
+    }
+
+    pub fn set_busy_timeout(&mut self, busy_timeout_millis: Option<u64>) {
+        self.busy_timeout_millis = busy_timeout_millis;
+    }
+
     pub fn get_device_clock(&self) -> SidClock {
         if self.use_native_device_clock {
             SidClock::OneMhz
@@ -661,8 +695,13 @@ impl HardsidUsbDevice {
 
     fn filter_reg_for_unsupported_writes(&mut self, dev_nr: i32, reg: u8) -> u8 {
         if self.number_of_sids > 1 && !self.are_multiple_sid_chips_supported(dev_nr) && reg >= 0x20 {
-            // ignore second SID chip for devices that don't support accessing multiple SID chip simultaneously
-            DUMMY_REG
+            if self.mono_downmix {
+                // mix the second SID chip into the first SID chip, voices of the SID chips overwrite each other
+                reg & 0x1f
+            } else {
+                // ignore second SID chip for devices that don't support accessing multiple SID chip simultaneously
+                DUMMY_REG
+            }
         } else {
             reg
         }
@@ -730,6 +769,10 @@ impl HardsidUsbDevice {
             };
 
             match device_state {
+                HSID_USB_STATE_BUSY if self.is_busy_timeout_reached() => {
+                    self.disconnect_with_error(ERROR_MSG_DEVICE_BUSY_TIMEOUT.to_string());
+                    return DeviceResponse::Error
+                },
                 HSID_USB_STATE_BUSY => {
                     self.sid_write_fifo.push_front(sid_write);
                     thread::yield_now();
@@ -739,7 +782,7 @@ impl HardsidUsbDevice {
                     self.disconnect_with_error(ERROR_MSG_DEVICE_FAILURE.to_string());
                     return DeviceResponse::Error
                 },
-                _ => ()
+                _ => self.busy_since = None
             };
 
             if self.is_aborted() {
@@ -899,7 +942,17 @@ impl HardsidUsbDevice {
             return true;
         }
 
-        if state != HSID_USB_STATE_BUSY || self.is_aborted() {
+        if state != HSID_USB_STATE_BUSY {
+            self.busy_since = None;
+            return true;
+        }
+
+        if self.is_aborted() {
+            return true;
+        }
+
+        if self.is_busy_timeout_reached() {
+            self.disconnect_with_error(ERROR_MSG_DEVICE_BUSY_TIMEOUT.to_string());
             return true;
         }
 
@@ -912,8 +965,70 @@ impl HardsidUsbDevice {
         false
     }
 
+    fn is_busy_timeout_reached(&mut self) -> bool {
+        let Some(busy_timeout_millis) = self.busy_timeout_millis else {
+            return false;
+        };
+
+        let busy_since = *self.busy_since.get_or_insert_with(time::Instant::now);
+        busy_since.elapsed().as_millis() as u64 >= busy_timeout_millis
+    }
+
     fn is_aborted(&self) -> bool {
         let abort_type = self.abort_type.load(Ordering::SeqCst);
         abort_type != ABORT_NO && abort_type != ABORTING
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn create_4u_and_uno_device() -> HardsidUsbDevice {
+        let mut device = HardsidUsbDevice::new(Arc::new(AtomicI32::new(0)));
+        let mut dev_type_count = [0u8; 4];
+        device.add_device(0, DEV_TYPE_HS_4U, 4, &mut dev_type_count);
+        device.add_device(1, DEV_TYPE_HS_UNO, 1, &mut dev_type_count);
+        device
+    }
+
+    fn get_device_names(device: &HardsidUsbDevice) -> Vec<String> {
+        (0..device.get_device_count()).map(|dev_nr| device.get_device_info(dev_nr).name).collect()
+    }
+
+    #[test]
+    fn names_each_socket_of_a_4u_and_an_uno() {
+        let device = create_4u_and_uno_device();
+
+        assert_eq!(get_device_names(&device), vec![
+            "HardSID 4U #1 socket 1",
+            "HardSID 4U #1 socket 2",
+            "HardSID 4U #1 socket 3",
+            "HardSID 4U #1 socket 4",
+            "HardSID Uno #1"
+        ]);
+    }
+
+    #[test]
+    fn numbers_devices_per_type() {
+        let mut device = create_4u_and_uno_device();
+        let mut dev_type_count = [0u8; 4];
+        dev_type_count[DEV_TYPE_HS_4U as usize] = 1;
+        dev_type_count[DEV_TYPE_HS_UNO as usize] = 1;
+        device.add_device(2, DEV_TYPE_HS_4U, 2, &mut dev_type_count);
+
+        let device_names = get_device_names(&device);
+        assert_eq!(device_names[5], "HardSID 4U #2 socket 1");
+        assert_eq!(device_names[6], "HardSID 4U #2 socket 2");
+    }
+
+    #[test]
+    fn maps_device_numbers_to_physical_devices() {
+        let mut device = create_4u_and_uno_device();
+
+        assert_eq!(device.device_id, vec![0, 0, 0, 0, 1]);
+        assert_eq!(device.device_base_reg, vec![0x00, 0x20, 0x40, 0x60, 0x00]);
+        assert!(device.can_pair_devices(0, 3));
+        assert!(!device.can_pair_devices(3, 4));
+    }
+}
@@ -0,0 +1,109 @@
+// Copyright (C) 2024 Wilfred Bos
+// Licensed under the GNU GPL v3 license. See the LICENSE file for the terms and conditions.
+
+use std::collections::{HashMap, VecDeque};
+
+const WINDOW_FRAMES: usize = 500;
+const MIN_LOOP_FRAMES: u32 = 250;
+const MAX_LOOP_FRAMES: u32 = 50 * 60 * 15;
+
+const FNV_OFFSET_BASIS: u64 = 0xcbf29ce484222325;
+const FNV_PRIME: u64 = 0x100000001b3;
+
+#[derive(Copy, Clone)]
+pub struct DetectedLoop {
+    pub start_in_millis: u32,
+    pub duration_in_millis: u32
+}
+
+// the SID register state is hashed once per frame, a loop is detected when the hashes of the last
+// window of frames are equal to the hashes of a window seen before
+pub struct LoopDetector {
+    cycles_per_frame: u32,
+    cycles_per_second: u32,
+    cycles_in_frame: u32,
+    frame_count: u32,
+    frame_hashes: VecDeque<u64>,
+    window_hashes: HashMap<u64, u32>,
+    detected: bool
+}
+
+impl LoopDetector {
+    pub fn new(cycles_per_frame: u32, cycles_per_second: u32) -> LoopDetector {
+        LoopDetector {
+            cycles_per_frame,
+            cycles_per_second,
+            cycles_in_frame: 0,
+            frame_count: 0,
+            frame_hashes: VecDeque::with_capacity(WINDOW_FRAMES),
+            window_hashes: HashMap::new(),
+            detected: false
+        }
+    }
+
+    pub fn init(&mut self) {
+        self.cycles_in_frame = 0;
+        self.frame_count = 0;
+        self.frame_hashes.clear();
+        self.window_hashes.clear();
+        self.detected = false;
+    }
+
+    pub fn add_cycles(&mut self, cycles: u32, sid_registers: &[u8; 256]) -> Option<DetectedLoop> {
+        if self.detected || self.frame_count >= MAX_LOOP_FRAMES + WINDOW_FRAMES as u32 {
+            return None;
+        }
+
+        self.cycles_in_frame += cycles;
+        if self.cycles_in_frame < self.cycles_per_frame {
+            return None;
+        }
+
+        let frame_hash = Self::hash(sid_registers);
+
+        while self.cycles_in_frame >= self.cycles_per_frame {
+            self.cycles_in_frame -= self.cycles_per_frame;
+
+            if let Some((start_frame, loop_frames)) = self.process_frame(frame_hash) {
+                self.detected = true;
+
+                let millis_per_frame = self.cycles_per_frame as f64 * 1000.0 / self.cycles_per_second as f64;
+                return Some(DetectedLoop {
+                    start_in_millis: (start_frame as f64 * millis_per_frame) as u32,
+                    duration_in_millis: (loop_frames as f64 * millis_per_frame) as u32
+                });
+            }
+        }
+        None
+    }
+
+    fn process_frame(&mut self, frame_hash: u64) -> Option<(u32, u32)> {
+        self.frame_count += 1;
+
+        if self.frame_hashes.len() == WINDOW_FRAMES {
+            self.frame_hashes.pop_front();
+        }
+        self.frame_hashes.push_back(frame_hash);
+
+        if self.frame_hashes.len() < WINDOW_FRAMES || self.frame_hashes.iter().all(|&hash| hash == frame_hash) {
+            return None;
+        }
+
+        let window_hash = self.frame_hashes.iter().fold(FNV_OFFSET_BASIS, |window_hash, &hash| (window_hash ^ hash).wrapping_mul(FNV_PRIME));
+
+        match self.window_hashes.get(&window_hash) {
+            Some(&frame) if self.frame_count - frame >= MIN_LOOP_FRAMES => {
+                Some((frame - WINDOW_FRAMES as u32, self.frame_count - frame))
+            },
+            Some(_) => None,
+            None => {
+                self.window_hashes.insert(window_hash, self.frame_count);
+                None
+            }
+        }
+    }
+
+    fn hash(sid_registers: &[u8; 256]) -> u64 {
+        sid_registers.iter().fold(FNV_OFFSET_BASIS, |hash, &data| (hash ^ data as u64).wrapping_mul(FNV_PRIME))
+    }
+}
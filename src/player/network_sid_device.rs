@@ -6,21 +6,22 @@ use std::net::{TcpStream, Shutdown, ToSocketAddrs};
 use std::sync::atomic::{Ordering, AtomicI32};
 use std::{sync::Arc, str, thread, time};
 
-use crate::utils::network;
-use super::sid_device::{DeviceId, DeviceInfo, DeviceResponse, DUMMY_REG, SamplingMethod, SidClock, SidDevice, SidModel};
+use crate::utils::{error_log, network};
+use super::sid_device::{DeviceFeature, DeviceId, DeviceInfo, DeviceResponse, DUMMY_REG, SamplingMethod, SidClock, SidDevice, SidModel};
 use super::{ABORT_NO, ABORTING, MIN_CYCLE_SID_WRITE};
 
 const WRITE_BUFFER_SIZE: usize = 1024;      // 1 KB maximum to avoid network overhead
 const RESPONSE_BUFFER_SIZE: usize = 260;
 const BUFFER_SINGLE_WRITE_SIZE: usize = 4;  // cycles 2 bytes, register 1 byte and data 1 byte
 const MAX_SID_WRITES: usize = WRITE_BUFFER_SIZE - BUFFER_SINGLE_WRITE_SIZE;
-const WRITE_CYCLES_THRESHOLD: u32 = 63 * 312 / 2;
-const CLIENT_WAIT_CYCLES_THRESHOLD: u32 = 20000;
+pub const DEFAULT_WRITE_CYCLES_THRESHOLD: u32 = 63 * 312 / 2;
+pub const DEFAULT_CLIENT_WAIT_CYCLES_THRESHOLD: u32 = 20000;
 const MIN_WAIT_TIME_BUSY_MILLIS: u64 = 3;
 const MIN_CYCLES_AFTER_DELAY: u16 = 0x100;
 const BUFFER_HEADER_SIZE: usize = 4;
 const DEFAULT_DEVICE_COUNT_INTERFACE_V1: i32 = 2;
-const SOCKET_CONNECTION_TIMEOUT: u64 = 1000;
+pub const DEFAULT_SOCKET_CONNECTION_TIMEOUT: u64 = 1000;
+const MAX_TRACED_BYTES: usize = 32;
 
 enum CommandResponse {
     Ok = 0,
@@ -34,7 +35,7 @@ enum CommandResponse {
 }
 
 #[allow(dead_code)]
-#[derive(Copy, Clone)]
+#[derive(Copy, Clone, Debug)]
 enum Command {
     Flush = 0,
     TrySetSidCount,
@@ -57,6 +58,15 @@ enum Command {
     SetSidHeader
 }
 
+pub struct NetworkDeviceSettings {
+    pub connection_timeout_millis: u64,
+    pub trace_protocol: bool,
+    pub write_cycles_threshold: u32,
+    pub client_wait_cycles_threshold: u32,
+    pub sid_variant: Option<String>,
+    pub device_number_given: bool
+}
+
 pub struct NetworkSidDeviceFacade {
     pub ns_device: NetworkSidDevice
 }
@@ -100,8 +110,8 @@ impl SidDevice for NetworkSidDeviceFacade {
         self.ns_device.set_sid_position(sid_position);
     }
 
-    fn set_sid_model(&mut self, dev_nr: i32, sid_socket: i32, _sid_model: SidModel) {
-        self.ns_device.set_sid_model(dev_nr, sid_socket);
+    fn set_sid_model(&mut self, dev_nr: i32, sid_socket: i32, sid_model: SidModel) {
+        self.ns_device.set_sid_model(dev_nr, sid_socket, sid_model);
     }
 
     fn set_sid_clock(&mut self, _dev_nr: i32, sid_clock: SidClock) {
@@ -144,6 +154,10 @@ impl SidDevice for NetworkSidDeviceFacade {
         self.ns_device.reset_all_buffers(0);
     }
 
+    fn clear_all_buffers(&mut self, _dev_nr: i32) {
+        self.ns_device.reset_all_buffers(0);
+    }
+
     fn enable_turbo_mode(&mut self, _dev_nr: i32) {
         self.ns_device.enable_turbo_mode();
     }
@@ -195,6 +209,14 @@ impl SidDevice for NetworkSidDeviceFacade {
     fn set_cycles_in_fifo(&mut self, _dev_nr: i32, _cycles: u32) {
         // not supported
     }
+
+    fn is_feature_supported(&mut self, _dev_nr: i32, feature: DeviceFeature) -> bool {
+        self.ns_device.is_feature_supported(feature)
+    }
+
+    fn get_buffer_fill(&mut self, _dev_nr: i32) -> Option<f32> {
+        Some(self.ns_device.get_buffer_fill())
+    }
 }
 
 pub struct NetworkSidDevice {
@@ -211,7 +233,15 @@ pub struct NetworkSidDevice {
     sampling_method: SamplingMethod,
     turbo_mode: bool,
     last_error: Option<String>,
-    abort_type: Arc<AtomicI32>
+    abort_type: Arc<AtomicI32>,
+    trace_protocol: bool,
+    last_command: Command,
+    write_cycles_threshold: u32,
+    client_wait_cycles_threshold: u32,
+    sid_variant: Option<String>,
+    sid_variant_nearest_match: bool,
+    sid_variant_warning_shown: bool,
+    config_names: Vec<String>
 }
 
 #[allow(dead_code)]
@@ -231,11 +261,34 @@ impl NetworkSidDevice {
             sampling_method: SamplingMethod::Best,
             turbo_mode: false,
             last_error: None,
-            abort_type
+            abort_type,
+            trace_protocol: false,
+            last_command: Command::Flush,
+            write_cycles_threshold: DEFAULT_WRITE_CYCLES_THRESHOLD,
+            client_wait_cycles_threshold: DEFAULT_CLIENT_WAIT_CYCLES_THRESHOLD,
+            sid_variant: None,
+            sid_variant_nearest_match: true,
+            sid_variant_warning_shown: false,
+            config_names: vec![]
         }
     }
 
-    pub fn connect(&mut self, host_name: &str, port: &str) -> Result<(), String> {
+    pub fn set_trace_protocol(&mut self, trace_protocol: bool) {
+        self.trace_protocol = trace_protocol;
+    }
+
+    // the nearest match is only used when no device number is given, otherwise the given device is kept
+    pub fn set_sid_variant(&mut self, sid_variant: Option<String>, nearest_match: bool) {
+        self.sid_variant = sid_variant;
+        self.sid_variant_nearest_match = nearest_match;
+    }
+
+    pub fn set_cycle_thresholds(&mut self, write_cycles_threshold: u32, client_wait_cycles_threshold: u32) {
+        self.write_cycles_threshold = write_cycles_threshold;
+        self.client_wait_cycles_threshold = client_wait_cycles_threshold;
+    }
+
+    pub fn connect(&mut self, host_name: &str, port: &str, connection_timeout_millis: u64) -> Result<(), String> {
         self.disconnect();
         self.last_error = None;
 
@@ -246,17 +299,31 @@ impl NetworkSidDevice {
         let mut addresses = [host_name, port].join(":").to_socket_addrs().unwrap();
 
         if let Some(socket_address) = addresses.find(|socket| socket.is_ipv4()) {
-            if let Ok(stream) = TcpStream::connect_timeout(&socket_address, time::Duration::from_millis(SOCKET_CONNECTION_TIMEOUT)) {
+            let stream = if connection_timeout_millis > 0 {
+                TcpStream::connect_timeout(&socket_address, time::Duration::from_millis(connection_timeout_millis))
+            } else {
+                TcpStream::connect(socket_address)
+            };
+
+            if let Ok(stream) = stream {
                 self.sid_device = Some(stream);
 
                 self.interface_version = self.get_version();
 
+                if self.trace_protocol {
+                    eprintln!("netsid: interface version {}", self.interface_version);
+                }
+
                 if self.interface_version >= 2 {
                     self.device_count = self.get_config_count();
                 } else {
                     self.device_count = DEFAULT_DEVICE_COUNT_INTERFACE_V1;
                 }
 
+                if self.sid_variant.is_some() {
+                    self.config_names = (0..self.device_count).map(|dev_nr| self.get_device_info(dev_nr).name).collect();
+                }
+
                 Ok(())
             } else {
                 Err(format!("Could not connect to: {}.", &socket_address))
@@ -285,6 +352,7 @@ impl NetworkSidDevice {
     }
 
     fn disconnect_with_error(&mut self, error_message: String) {
+        error_log::write(&error_message);
         self.last_error = Some(error_message);
         self.disconnect();
     }
@@ -361,7 +429,17 @@ impl NetworkSidDevice {
         }
     }
 
-    pub fn set_sid_model(&mut self, dev_nr: i32, sid_socket: i32) {
+    pub fn set_sid_model(&mut self, dev_nr: i32, sid_socket: i32, sid_model: SidModel) {
+        let config_index = self.find_sid_variant_config(sid_model);
+
+        if config_index.is_none() && !self.sid_variant_nearest_match && !self.sid_variant_warning_shown {
+            if let Some(sid_variant) = &self.sid_variant {
+                eprintln!("Warning: SID variant '{sid_variant}' is not offered by the network device, device {} is used.", dev_nr + 1);
+                self.sid_variant_warning_shown = true;
+            }
+        }
+
+        let dev_nr = config_index.unwrap_or(dev_nr);
         self.sid_model = dev_nr;
 
         if self.interface_version >= 2 && dev_nr < self.device_count {
@@ -369,11 +447,30 @@ impl NetworkSidDevice {
         }
     }
 
+    fn find_sid_variant_config(&self, sid_model: SidModel) -> Option<i32> {
+        let sid_variant = self.sid_variant.as_ref()?.to_lowercase();
+        let model_name = match sid_model {
+            SidModel::Mos6581 => "6581",
+            SidModel::Mos8580 => "8580"
+        };
+
+        let config_names = self.config_names.iter().map(|name| name.to_lowercase()).collect::<Vec<String>>();
+
+        let config_index = config_names.iter().position(|name| name.contains(&sid_variant) && name.contains(model_name));
+        if config_index.is_some() || !self.sid_variant_nearest_match {
+            return config_index.map(|config_index| config_index as i32);
+        }
+
+        config_names.iter().position(|name| name.contains(&sid_variant))
+            .or_else(|| config_names.iter().position(|name| name.contains(model_name)))
+            .map(|config_index| config_index as i32)
+    }
+
     pub fn set_sid_clock(&mut self, sid_clock: SidClock) {
-        self.sid_clock = sid_clock;
+        self.sid_clock = Self::get_protocol_clock(sid_clock);
 
         if self.interface_version >= 2 {
-            self.try_flush_buffer(Command::TrySetClock, 0, Some(&[sid_clock as u8]));
+            self.try_flush_buffer(Command::TrySetClock, 0, Some(&[self.sid_clock as u8]));
         }
     }
 
@@ -385,6 +482,19 @@ impl NetworkSidDevice {
         }
     }
 
+    pub fn is_feature_supported(&mut self, feature: DeviceFeature) -> bool {
+        match feature {
+            DeviceFeature::MultipleSids => self.are_multiple_sid_chips_supported(),
+            DeviceFeature::SidPosition | DeviceFeature::SamplingMethod | DeviceFeature::SidClock => self.interface_version >= 2,
+            DeviceFeature::ExtendedSidClock => false,
+            DeviceFeature::FadeIn => self.interface_version >= 4
+        }
+    }
+
+    pub fn get_buffer_fill(&self) -> f32 {
+        (self.buffer_cycles as f32 / self.write_cycles_threshold as f32).min(1.0)
+    }
+
     pub fn set_sid_header(&mut self, sid_header: Vec<u8>) {
         if self.interface_version >= 4 {
             self.try_flush_buffer(Command::SetSidHeader, 0, Some(&sid_header));
@@ -530,7 +640,7 @@ impl NetworkSidDevice {
         let cycles = self.delay(dev_nr, cycles);
         self.add_to_buffer(reg, data, cycles);
 
-        if (self.buffer_index >= MAX_SID_WRITES) || (self.buffer_cycles >= WRITE_CYCLES_THRESHOLD) {
+        if (self.buffer_index >= MAX_SID_WRITES) || (self.buffer_cycles >= self.write_cycles_threshold) {
             self.force_flush(dev_nr);
         }
         DeviceResponse::Ok
@@ -540,7 +650,7 @@ impl NetworkSidDevice {
         let cycles = self.delay(dev_nr, cycles);
         self.add_to_buffer(reg, data, cycles);
 
-        if (self.buffer_index >= MAX_SID_WRITES) || (self.buffer_cycles >= WRITE_CYCLES_THRESHOLD) {
+        if (self.buffer_index >= MAX_SID_WRITES) || (self.buffer_cycles >= self.write_cycles_threshold) {
             self.try_write_buffer(Command::TryWrite, dev_nr, None)
         } else {
             DeviceResponse::Ok
@@ -573,7 +683,7 @@ impl NetworkSidDevice {
 
             match device_state {
                 CommandResponse::Ok => {
-                    if cycles_sent_to_server > CLIENT_WAIT_CYCLES_THRESHOLD {
+                    if cycles_sent_to_server > self.client_wait_cycles_threshold {
                         thread::sleep(time::Duration::from_millis(MIN_WAIT_TIME_BUSY_MILLIS));
                     }
                     DeviceResponse::Ok
@@ -601,6 +711,15 @@ impl NetworkSidDevice {
         self.sid_clock
     }
 
+    // the network protocol only knows PAL, NTSC and 1 MHz, so the device clock reports the clock actually used
+    fn get_protocol_clock(sid_clock: SidClock) -> SidClock {
+        match sid_clock {
+            SidClock::NtscOld => SidClock::Ntsc,
+            SidClock::Drean => SidClock::Pal,
+            _ => sid_clock
+        }
+    }
+
     fn convert_device_number(&mut self, dev_nr: i32) -> i32 {
         if self.interface_version == 1 {
             return (self.sid_model & 0x01) | (self.sid_clock as i32) << 1 | (self.sampling_method as i32) << 2;
@@ -695,7 +814,7 @@ impl NetworkSidDevice {
                 } else {
                     if !self.turbo_mode {
                         if let Command::TryWrite = command {
-                            if cycles_sent_to_server > CLIENT_WAIT_CYCLES_THRESHOLD {
+                            if cycles_sent_to_server > self.client_wait_cycles_threshold {
                                 thread::sleep(time::Duration::from_millis(1));
                             }
                         }
@@ -711,6 +830,11 @@ impl NetworkSidDevice {
     fn flush_buffer(&mut self) -> (CommandResponse, Vec<u8>) {
         self.set_data_length(self.buffer_index);
 
+        if self.trace_protocol {
+            eprintln!("netsid: > {:?} {} ({} bytes) {:02x?}", self.last_command, self.write_buffer[1], self.buffer_index,
+                &self.write_buffer[BUFFER_HEADER_SIZE..self.buffer_index.min(BUFFER_HEADER_SIZE + MAX_TRACED_BYTES)]);
+        }
+
         let response = self.send_data();
 
         if let CommandResponse::Error = response {
@@ -782,6 +906,10 @@ impl NetworkSidDevice {
     fn handle_response(&mut self, result_size: usize) -> (CommandResponse, Vec<u8>) {
         let response = self.response_buffer[0];
 
+        if self.trace_protocol {
+            eprintln!("netsid: < {} {:02x?}", Self::get_response_name(response), &self.response_buffer[1..result_size]);
+        }
+
         if response == CommandResponse::Busy as u8 {
             return (CommandResponse::Busy, vec![0]);
         }
@@ -806,6 +934,19 @@ impl NetworkSidDevice {
         (CommandResponse::Error, vec![0])
     }
 
+    fn get_response_name(response: u8) -> &'static str {
+        match response {
+            0 => "Ok",
+            1 => "Busy",
+            2 => "Error",
+            3 => "Read",
+            4 => "Version",
+            5 => "Count",
+            6 => "Info",
+            _ => "Unknown"
+        }
+    }
+
     fn reset_buffer(&mut self) {
         self.buffer_index = BUFFER_HEADER_SIZE;
         self.buffer_cycles = 0;
@@ -817,6 +958,7 @@ impl NetworkSidDevice {
     }
 
     fn set_command(&mut self, command: Command, argument: u8, optional_arguments: Option<&[u8]>) {
+        self.last_command = command;
         self.write_buffer[0] = command as u8;
         self.write_buffer[1] = argument;
         self.write_buffer[2] = 0;
@@ -0,0 +1,113 @@
+// Copyright (C) 2024 Wilfred Bos
+// Licensed under the GNU GPL v3 license. See the LICENSE file for the terms and conditions.
+
+use std::fmt::Write as _;
+use super::sid_device::DUMMY_REG;
+
+const MAX_NUMBER_OF_SIDS: usize = 8;
+const SID_REGISTER_COUNT: u8 = 0x20;
+const VOICE_CONTROL_REGISTERS: [u8; 3] = [0x04, 0x0b, 0x12];
+const WAVEFORM_NAMES: [&str; 4] = ["triangle", "sawtooth", "pulse", "noise"];
+const HISTOGRAM_WIDTH: u32 = 40;
+
+pub struct RegisterStatistics {
+    register_writes: [u32; 256],
+    voice_waveforms: [u8; MAX_NUMBER_OF_SIDS * 3],
+    voice_gated: [bool; MAX_NUMBER_OF_SIDS * 3],
+    total_writes: u64,
+    total_cycles: u64,
+    cycles_per_frame: u32,
+    cycles_in_frame: u32,
+    writes_in_frame: u32,
+    peak_writes_per_frame: u32
+}
+
+impl RegisterStatistics {
+    pub fn new(cycles_per_frame: u32) -> RegisterStatistics {
+        RegisterStatistics {
+            register_writes: [0; 256],
+            voice_waveforms: [0; MAX_NUMBER_OF_SIDS * 3],
+            voice_gated: [false; MAX_NUMBER_OF_SIDS * 3],
+            total_writes: 0,
+            total_cycles: 0,
+            cycles_per_frame,
+            cycles_in_frame: 0,
+            writes_in_frame: 0,
+            peak_writes_per_frame: 0
+        }
+    }
+
+    pub fn add_cycles(&mut self, cycles: u32) {
+        self.total_cycles += cycles as u64;
+        self.cycles_in_frame += cycles;
+
+        while self.cycles_in_frame >= self.cycles_per_frame {
+            self.cycles_in_frame -= self.cycles_per_frame;
+            self.peak_writes_per_frame = self.peak_writes_per_frame.max(self.writes_in_frame);
+            self.writes_in_frame = 0;
+        }
+    }
+
+    pub fn process_write(&mut self, cycles: u32, reg: u8, data: u8) {
+        self.add_cycles(cycles);
+
+        if reg == DUMMY_REG {
+            return;
+        }
+
+        self.register_writes[reg as usize] += 1;
+        self.total_writes += 1;
+        self.writes_in_frame += 1;
+
+        let sid_nr = (reg / SID_REGISTER_COUNT) as usize;
+        if let Some(voice_nr) = VOICE_CONTROL_REGISTERS.iter().position(|&control_reg| control_reg == reg % SID_REGISTER_COUNT) {
+            let voice_index = sid_nr * 3 + voice_nr;
+            if voice_index < self.voice_waveforms.len() {
+                self.voice_waveforms[voice_index] |= data >> 4;
+                self.voice_gated[voice_index] |= data & 0x01 != 0;
+            }
+        }
+    }
+
+    pub fn get_report(&self, cycles_per_second: u32, number_of_sids: i32) -> String {
+        let seconds = self.total_cycles as f64 / cycles_per_second as f64;
+        let writes_per_second = if seconds > 0.0 { self.total_writes as f64 / seconds } else { 0.0 };
+        let peak_writes_per_frame = self.peak_writes_per_frame.max(self.writes_in_frame);
+
+        let mut report = String::new();
+        let _ = writeln!(report, "SID register statistics:");
+        let _ = writeln!(report, "Total writes        : {}", self.total_writes);
+        let _ = writeln!(report, "Emulated time       : {seconds:.1} seconds");
+        let _ = writeln!(report, "Writes per second   : {writes_per_second:.1}");
+        let _ = writeln!(report, "Peak writes/frame   : {peak_writes_per_frame}");
+
+        let max_writes = *self.register_writes.iter().max().unwrap_or(&0);
+        if max_writes > 0 {
+            let _ = writeln!(report, "\nWrites per register:");
+
+            for (reg, &writes) in self.register_writes.iter().enumerate().filter(|(_, &writes)| writes > 0) {
+                let bar_length = ((writes as u64 * HISTOGRAM_WIDTH as u64).div_ceil(max_writes as u64)) as usize;
+                let _ = writeln!(report, "SID {} ${:02X}: {writes:>8} {}", reg / SID_REGISTER_COUNT as usize + 1, reg % SID_REGISTER_COUNT as usize, "#".repeat(bar_length));
+            }
+        }
+
+        let _ = writeln!(report, "\nVoices:");
+        for voice_index in 0..(number_of_sids.max(1) as usize * 3).min(self.voice_waveforms.len()) {
+            let waveforms = WAVEFORM_NAMES.iter()
+                .enumerate()
+                .filter(|(bit, _)| self.voice_waveforms[voice_index] & (1 << bit) != 0)
+                .map(|(_, name)| *name)
+                .collect::<Vec<&str>>();
+
+            let usage = if !self.voice_gated[voice_index] {
+                "not used".to_string()
+            } else if waveforms.is_empty() {
+                "no waveform".to_string()
+            } else {
+                waveforms.join(", ")
+            };
+            let _ = writeln!(report, "SID {} voice {}: {usage}", voice_index / 3 + 1, voice_index % 3 + 1);
+        }
+        report
+    }
+}
@@ -7,6 +7,7 @@ use std::time::Instant;
 
 const PAL_CYCLES_PER_SECOND: f64 = 17_734_475.0 / 18.0;    // = 0985248,611 = ~ 312 * 63 * 50;
 const NTSC_CYCLES_PER_SECOND: f64 = 14_318_180.0 / 14.0;   // = 1022727,143 = ~ 263 * 65 * 60;
+const DREAN_CYCLES_PER_SECOND: f64 = 14_328_225.0 / 14.0;  // = 1023444,643 = ~ 312 * 65 * 50,46;
 const ONE_MHZ_CYCLES_PER_SECOND: f64 = 1_000_000.0;
 
 #[derive(Copy, Clone)]
@@ -40,6 +41,7 @@ pub struct SidDataProcessor {
     current_sid_write: Option<SidWrite>,
     current_time: Option<Instant>,
     cycles_per_second: f64,
+    display_cycles_per_second: f64,    // cycles per second of the tune clock, used for the time of the tune
     next_time_in_micros: u128
 }
 
@@ -57,6 +59,7 @@ impl SidDataProcessor {
             current_sid_write: None,
             current_time: None,
             cycles_per_second: PAL_CYCLES_PER_SECOND,
+            display_cycles_per_second: PAL_CYCLES_PER_SECOND,
             next_time_in_micros: 0
         }
     }
@@ -103,8 +106,12 @@ impl SidDataProcessor {
         self.cycles_in_fifo
     }
 
+    pub fn set_time_in_millis(&mut self, time_in_millis: u32) {
+        self.init((time_in_millis as f64 * self.display_cycles_per_second / 1000.0).round() as u32);
+    }
+
     pub fn get_time_in_millis(&self) -> u32 {
-        (self.time_in_cycles as f64 / (self.cycles_per_second / 1000.0)).round() as u32
+        (self.time_in_cycles as f64 / (self.display_cycles_per_second / 1000.0)).round() as u32
     }
 
     fn process_write(&mut self, reg: u8, data: u8, cycles: u32, cycles_real: u32) {
@@ -179,6 +186,10 @@ impl SidDataProcessor {
         self.cycles_per_second = Self::get_cycles_per_second(sid_clock);
     }
 
+    pub fn set_display_clock(&mut self, sid_clock: SidClock) {
+        self.display_cycles_per_second = Self::get_cycles_per_second(sid_clock);
+    }
+
     pub fn is_note_finished(&mut self, reg_base: u8) -> bool {
         static ENV_DECAY_RELEASE_IN_CYCLES: [u32; 16] = [
             (0x0009 * 3) << 8, // ~6ms
@@ -223,8 +234,81 @@ impl SidDataProcessor {
     pub fn get_cycles_per_second(sid_clock: SidClock) -> f64 {
         match sid_clock {
             SidClock::Pal => PAL_CYCLES_PER_SECOND,
-            SidClock::Ntsc => NTSC_CYCLES_PER_SECOND,
+            SidClock::Ntsc | SidClock::NtscOld => NTSC_CYCLES_PER_SECOND,
+            SidClock::Drean => DREAN_CYCLES_PER_SECOND,
             _ => ONE_MHZ_CYCLES_PER_SECOND
         }
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::env;
+    use std::thread;
+    use std::time::Duration;
+
+    const CYCLES_PER_FRAME: u32 = 312 * 63;
+    const FRAMES_IN_BUFFER: u32 = 10;
+    const DEFAULT_TIMING_TEST_SECONDS: u32 = 10;
+    const DEFAULT_TIMING_TOLERANCE_MILLIS: u32 = 20;
+
+    fn get_env_value(name: &str, default_value: u32) -> u32 {
+        env::var(name).ok().and_then(|value| value.parse().ok()).unwrap_or(default_value)
+    }
+
+    #[test]
+    fn converts_time_in_millis() {
+        let mut sid_data_processor = SidDataProcessor::new();
+        sid_data_processor.set_time_in_millis(12_345);
+        assert_eq!(sid_data_processor.get_time_in_millis(), 12_345);
+    }
+
+    #[test]
+    fn time_follows_tune_clock_on_1mhz_device() {
+        let mut sid_data_processor = SidDataProcessor::new();
+        sid_data_processor.set_sid_clock(SidClock::OneMhz);
+        sid_data_processor.set_display_clock(SidClock::Pal);
+
+        let tune_cycles = PAL_CYCLES_PER_SECOND.round() as u32;
+        sid_data_processor.process_write(0x18, 0x0f, ONE_MHZ_CYCLES_PER_SECOND as u32, tune_cycles);
+        assert_eq!(sid_data_processor.get_time_in_millis(), 1000);
+    }
+
+    #[test]
+    fn time_follows_ntsc_tune_clock_on_pal_device() {
+        let mut sid_data_processor = SidDataProcessor::new();
+        sid_data_processor.set_sid_clock(SidClock::Pal);
+        sid_data_processor.set_display_clock(SidClock::Ntsc);
+
+        let tune_cycles = (NTSC_CYCLES_PER_SECOND * 2.0).round() as u32;
+        sid_data_processor.process_write(0x18, 0x0f, (PAL_CYCLES_PER_SECOND * 2.0) as u32, tune_cycles);
+        assert_eq!(sid_data_processor.get_time_in_millis(), 2000);
+    }
+
+    // run with: cargo test -- --ignored, the duration and tolerance can be set with the
+    // ACID64C_TIMING_TEST_SECONDS and ACID64C_TIMING_TOLERANCE_MILLIS environment variables
+    #[test]
+    #[ignore]
+    fn time_tracks_wall_clock() {
+        let test_seconds = get_env_value("ACID64C_TIMING_TEST_SECONDS", DEFAULT_TIMING_TEST_SECONDS);
+        let tolerance_millis = get_env_value("ACID64C_TIMING_TOLERANCE_MILLIS", DEFAULT_TIMING_TOLERANCE_MILLIS);
+
+        let mut sid_data_processor = SidDataProcessor::new();
+        sid_data_processor.init(0);
+
+        let start_time = Instant::now();
+        while sid_data_processor.get_time_in_millis() < test_seconds * 1000 {
+            while sid_data_processor.get_cycles_in_fifo() < CYCLES_PER_FRAME * FRAMES_IN_BUFFER {
+                sid_data_processor.write(CYCLES_PER_FRAME, 0x18, 0x0f, CYCLES_PER_FRAME);
+            }
+
+            sid_data_processor.process_sid_write_fifo();
+            thread::sleep(Duration::from_millis(sid_data_processor.get_next_event_in_millis()));
+        }
+
+        let wall_clock_millis = start_time.elapsed().as_millis() as i64;
+        let drift_millis = (sid_data_processor.get_time_in_millis() as i64 - wall_clock_millis).abs();
+        assert!(drift_millis <= tolerance_millis as i64, "time drifted {drift_millis} ms from the wall clock after {test_seconds} seconds");
+    }
+}
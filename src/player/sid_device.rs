@@ -1,20 +1,22 @@
 // Copyright (C) 2020 - 2023 Wilfred Bos
 // Licensed under the GNU GPL v3 license. See the LICENSE file for the terms and conditions.
 
-#[derive(Copy, Clone, Eq, PartialEq)]
+#[derive(Copy, Clone, Debug, Eq, PartialEq)]
 pub enum SidClock {
     Pal = 0,
     Ntsc = 1,
-    OneMhz = 2
+    OneMhz = 2,
+    NtscOld = 3,
+    Drean = 4
 }
 
+#[derive(Copy, Clone)]
 pub enum SidModel {
     Mos6581 = 0,
     Mos8580 = 1
 }
 
-#[allow(dead_code)]
-#[derive(Copy, Clone)]
+#[derive(Copy, Clone, Debug)]
 pub enum SamplingMethod {
     Best = 0,
     Fast = 1
@@ -27,7 +29,7 @@ pub enum DeviceResponse {
     Error = 2
 }
 
-#[derive(Copy, Clone, Eq, PartialEq)]
+#[derive(Copy, Clone, Debug, Eq, PartialEq)]
 pub enum DeviceId {
     HardsidUsb = 0,
     NetworkSidDevice = 1,
@@ -35,6 +37,16 @@ pub enum DeviceId {
     SidBlaster = 3
 }
 
+#[derive(Copy, Clone, Eq, PartialEq)]
+pub enum DeviceFeature {
+    MultipleSids,
+    SidPosition,
+    SamplingMethod,
+    SidClock,
+    ExtendedSidClock,
+    FadeIn
+}
+
 #[derive(Clone)]
 #[allow(dead_code)]
 pub struct DeviceInfo {
@@ -87,6 +99,8 @@ pub trait SidDevice {
 
     fn reset_all_buffers(&mut self, dev_nr: i32);
 
+    fn clear_all_buffers(&mut self, dev_nr: i32);
+
     fn enable_turbo_mode(&mut self, dev_nr: i32);
 
     fn disable_turbo_mode(&mut self, dev_nr: i32);
@@ -112,4 +126,8 @@ pub trait SidDevice {
     fn stop_sid(&mut self, dev_nr: i32);
 
     fn set_cycles_in_fifo(&mut self, dev_nr: i32, cycles: u32);
+
+    fn is_feature_supported(&mut self, dev_nr: i32, feature: DeviceFeature) -> bool;
+
+    fn get_buffer_fill(&mut self, dev_nr: i32) -> Option<f32>;
 }
@@ -1,9 +1,9 @@
 // Copyright (C) 2020 - 2023 Wilfred Bos
 // Licensed under the GNU GPL v3 license. See the LICENSE file for the terms and conditions.
 
-use super::sid_device::{DeviceId, DeviceInfo, DeviceResponse, SamplingMethod, SidClock, SidDevice};
+use super::sid_device::{DeviceFeature, DeviceId, DeviceInfo, DeviceResponse, SamplingMethod, SidClock, SidDevice};
 use super::hardsid_usb_device::{HardsidUsbDevice, HardsidUsbDeviceFacade};
-use super::network_sid_device::{NetworkSidDevice, NetworkSidDeviceFacade};
+use super::network_sid_device::{NetworkDeviceSettings, NetworkSidDevice, NetworkSidDeviceFacade};
 use super::sidblaster_usb_device::{SidBlasterUsbDevice, SidBlasterUsbDeviceFacade};
 use super::ultimate_device::{UltimateDevice, UltimateDeviceFacade};
 use super::sid_device::SidModel;
@@ -11,6 +11,12 @@ use super::sid_device::SidModel;
 use std::sync::atomic::AtomicI32;
 use std::sync::Arc;
 
+pub enum DeviceError {
+    NoHardware(String),
+    Unreachable(String),
+    NotConfigured
+}
+
 pub struct SidDevicesFacade {
     pub devices: SidDevices
 }
@@ -98,6 +104,10 @@ impl SidDevice for SidDevicesFacade {
         self.devices.reset_all_buffers(dev_nr);
     }
 
+    fn clear_all_buffers(&mut self, dev_nr: i32) {
+        self.devices.clear_all_buffers(dev_nr);
+    }
+
     fn enable_turbo_mode(&mut self, dev_nr: i32) {
         self.devices.enable_turbo_mode(dev_nr);
     }
@@ -149,6 +159,14 @@ impl SidDevice for SidDevicesFacade {
     fn set_cycles_in_fifo(&mut self, dev_nr: i32, cycles: u32) {
         self.devices.set_cycles_in_fifo(dev_nr, cycles);
     }
+
+    fn is_feature_supported(&mut self, dev_nr: i32, feature: DeviceFeature) -> bool {
+        self.devices.is_feature_supported(dev_nr, feature)
+    }
+
+    fn get_buffer_fill(&mut self, dev_nr: i32) -> Option<f32> {
+        self.devices.get_buffer_fill(dev_nr)
+    }
 }
 
 pub struct SidDevices {
@@ -160,7 +178,9 @@ pub struct SidDevices {
     device_offset: Vec<u8>,
     abort_type: Arc<AtomicI32>,
     use_native_device_clock: bool,
-    errors: Vec<String>
+    balance: i32,
+    sid_count: i32,
+    errors: Vec<DeviceError>
 }
 
 #[allow(dead_code)]
@@ -175,37 +195,39 @@ impl SidDevices {
             device_offset: vec![],
             abort_type,
             use_native_device_clock: true,
+            balance: 0,
+            sid_count: 0,
             errors: vec![]
         }
     }
 
-    pub fn connect_hardsid_device(mut self) -> Self {
-        let hs_connect_result = self.try_connect_hardsid_device();
+    pub fn connect_hardsid_device(mut self, mono_downmix: bool, busy_timeout_millis: Option<u64>) -> Self {
+        let hs_connect_result = self.try_connect_hardsid_device(mono_downmix, busy_timeout_millis);
 
         if let Err(hs_connection_result) = hs_connect_result {
-            self.errors.push(hs_connection_result);
+            self.errors.push(DeviceError::NoHardware(hs_connection_result));
         } else {
             self.set_native_device_clock(self.use_native_device_clock);
         }
         self
     }
 
-    pub fn connect_sidblaster(mut self) -> Self {
-        let sb_connect_result = self.try_connect_sidblaster_device();
+    pub fn connect_sidblaster(mut self, latency_millis: u64, mono_downmix: bool) -> Self {
+        let sb_connect_result = self.try_connect_sidblaster_device(latency_millis, mono_downmix);
 
         if let Err(sb_connect_result) = sb_connect_result {
-            self.errors.push(sb_connect_result);
+            self.errors.push(DeviceError::NoHardware(sb_connect_result));
         } else {
             self.set_native_device_clock(self.use_native_device_clock);
         }
         self
     }
 
-    pub fn connect_network_device(mut self, ip_address: &str, port: &str) -> Self {
-        let ns_connect_result = self.try_connect_network_device(ip_address, port);
+    pub fn connect_network_device(mut self, ip_address: &str, port: &str, settings: &NetworkDeviceSettings) -> Self {
+        let ns_connect_result = self.try_connect_network_device(ip_address, port, settings);
 
         if let Err(ns_connect_result) = ns_connect_result {
-            self.errors.push(ns_connect_result);
+            self.errors.push(DeviceError::Unreachable(ns_connect_result));
         }
         self
     }
@@ -214,7 +236,11 @@ impl SidDevices {
         let us_connect_result = self.try_connect_ultimate_device(ip_address, port);
 
         if let Err(us_connect_result) = us_connect_result {
-            self.errors.push(us_connect_result);
+            if ip_address.is_empty() {
+                self.errors.push(DeviceError::NotConfigured);
+            } else {
+                self.errors.push(DeviceError::Unreachable(us_connect_result));
+            }
         }
         self
     }
@@ -228,11 +254,31 @@ impl SidDevices {
     }
 
     pub fn errors(&mut self) -> String {
-        self.errors.join(" | ")
+        let mut no_hardware = vec![];
+        let mut unreachable = vec![];
+
+        for error in &self.errors {
+            match error {
+                DeviceError::NoHardware(message) => no_hardware.push(message.as_str()),
+                DeviceError::Unreachable(message) => unreachable.push(message.as_str()),
+                DeviceError::NotConfigured => ()
+            }
+        }
+
+        let mut summary = "No SID devices available.".to_string();
+        if !no_hardware.is_empty() {
+            summary += &format!("\n  Hardware: {}", no_hardware.join(" "));
+        }
+        if !unreachable.is_empty() {
+            summary += &format!("\n  Network : {}", unreachable.join(" "));
+        }
+        summary
     }
 
-    fn try_connect_hardsid_device(&mut self) -> Result<(), String> {
+    fn try_connect_hardsid_device(&mut self, mono_downmix: bool, busy_timeout_millis: Option<u64>) -> Result<(), String> {
         let mut hs_device = HardsidUsbDevice::new(Arc::clone(&self.abort_type));
+        hs_device.set_mono_downmix(mono_downmix);
+        hs_device.set_busy_timeout(busy_timeout_millis);
         let hs_connect_result = hs_device.connect();
         if hs_connect_result.is_ok() {
             let sid_count = hs_device.get_device_count();
@@ -247,9 +293,10 @@ impl SidDevices {
         }
     }
 
-    fn try_connect_sidblaster_device(&mut self) -> Result<(), String> {
+    fn try_connect_sidblaster_device(&mut self, latency_millis: u64, mono_downmix: bool) -> Result<(), String> {
         let mut sb_device = SidBlasterUsbDevice::new(Arc::clone(&self.abort_type));
-        let sb_connect_result = sb_device.connect();
+        sb_device.set_mono_downmix(mono_downmix);
+        let sb_connect_result = sb_device.connect(latency_millis);
         if sb_connect_result.is_ok() {
             let sid_count = sb_device.get_device_count();
             let sb_facade = SidBlasterUsbDeviceFacade { sb_device };
@@ -263,9 +310,12 @@ impl SidDevices {
         }
     }
 
-    fn try_connect_network_device(&mut self, ip_address: &str, port: &str) -> Result<(), String> {
+    fn try_connect_network_device(&mut self, ip_address: &str, port: &str, settings: &NetworkDeviceSettings) -> Result<(), String> {
         let mut ns_device = NetworkSidDevice::new(Arc::clone(&self.abort_type));
-        let ns_connect_result = ns_device.connect(ip_address, port);
+        ns_device.set_trace_protocol(settings.trace_protocol);
+        ns_device.set_cycle_thresholds(settings.write_cycles_threshold, settings.client_wait_cycles_threshold);
+        ns_device.set_sid_variant(settings.sid_variant.clone(), !settings.device_number_given);
+        let ns_connect_result = ns_device.connect(ip_address, port, settings.connection_timeout_millis);
         if ns_connect_result.is_ok() {
             let sid_count = ns_device.get_device_count();
             let ns_facade = NetworkSidDeviceFacade { ns_device };
@@ -307,6 +357,15 @@ impl SidDevices {
         self.device_count += device_count;
     }
 
+    pub fn prefer_device_type(&mut self, device_type: DeviceId) {
+        let mut device_order = (0..self.device_count).collect::<Vec<i32>>();
+        device_order.sort_by_key(|&dev_nr| self.get_device_id(dev_nr) != device_type);
+
+        self.device_name = device_order.iter().map(|&dev_nr| self.device_name[dev_nr as usize].clone()).collect();
+        self.device_mapping_id = device_order.iter().map(|&dev_nr| self.device_mapping_id[dev_nr as usize]).collect();
+        self.device_offset = device_order.iter().map(|&dev_nr| self.device_offset[dev_nr as usize]).collect();
+    }
+
     #[inline]
     fn map_device(&mut self, dev_nr: i32) -> u8 {
         self.device_mapping_id[dev_nr as usize]
@@ -422,6 +481,7 @@ impl SidDevices {
     }
 
     pub fn set_sid_count(&mut self, dev_nr: i32, sid_count: i32) {
+        self.sid_count = sid_count;
         let mapped_dev_nr = self.map_device(dev_nr);
         let mapped_sid_nr = self.map_sid_offset(dev_nr);
         self.sid_devices[mapped_dev_nr as usize].set_sid_count(mapped_sid_nr as i32, sid_count);
@@ -499,6 +559,12 @@ impl SidDevices {
         self.sid_devices[mapped_dev_nr as usize].reset_all_buffers(mapped_sid_nr as i32);
     }
 
+    pub fn clear_all_buffers(&mut self, dev_nr: i32) {
+        let mapped_dev_nr = self.map_device(dev_nr);
+        let mapped_sid_nr = self.map_sid_offset(dev_nr);
+        self.sid_devices[mapped_dev_nr as usize].clear_all_buffers(mapped_sid_nr as i32);
+    }
+
     pub fn enable_turbo_mode(&mut self, dev_nr: i32) {
         let mapped_dev_nr = self.map_device(dev_nr);
         let mapped_sid_nr = self.map_sid_offset(dev_nr);
@@ -520,15 +586,39 @@ impl SidDevices {
     pub fn write(&mut self, dev_nr: i32, cycles: u32, reg: u8, data: u8) -> DeviceResponse {
         let mapped_dev_nr = self.map_device(dev_nr);
         let mapped_sid_nr = self.map_sid_offset(dev_nr);
+        let data = self.apply_balance(reg, data);
         self.sid_devices[mapped_dev_nr as usize].write(mapped_sid_nr as i32, cycles, reg, data)
     }
 
     fn try_write(&mut self, dev_nr: i32, cycles: u32, reg: u8, data: u8) -> DeviceResponse {
         let mapped_dev_nr = self.map_device(dev_nr);
         let mapped_sid_nr = self.map_sid_offset(dev_nr);
+        let data = self.apply_balance(reg, data);
         self.sid_devices[mapped_dev_nr as usize].try_write(mapped_sid_nr as i32, cycles, reg, data)
     }
 
+    pub fn set_balance(&mut self, balance: i32) {
+        self.balance = balance.clamp(-100, 100);
+    }
+
+    fn apply_balance(&self, reg: u8, data: u8) -> u8 {
+        // balance only applies to stereo tunes, where the first SID is mapped to the left device and the second SID to the right device
+        if self.balance == 0 || self.sid_count != 2 || reg & 0x1f != 0x18 {
+            return data;
+        }
+
+        let is_right_channel = reg >> 5 == 1;
+        let attenuation = if is_right_channel {
+            (-self.balance).max(0)
+        } else {
+            self.balance.max(0)
+        };
+
+        let volume = (data & 0x0f) as i32;
+        let volume = ((volume * (100 - attenuation) + 50) / 100).clamp(0, 0x0f) as u8;
+        (data & 0xf0) | volume
+    }
+
     fn retry_write(&mut self, dev_nr: i32) -> DeviceResponse {
         let mapped_dev_nr = self.map_device(dev_nr);
         let mapped_sid_nr = self.map_sid_offset(dev_nr);
@@ -577,4 +667,16 @@ impl SidDevices {
         let mapped_sid_nr = self.map_sid_offset(dev_nr);
         self.sid_devices[mapped_dev_nr as usize].set_cycles_in_fifo(mapped_sid_nr as i32, cycles);
     }
+
+    pub fn is_feature_supported(&mut self, dev_nr: i32, feature: DeviceFeature) -> bool {
+        let mapped_dev_nr = self.map_device(dev_nr);
+        let mapped_sid_nr = self.map_sid_offset(dev_nr);
+        self.sid_devices[mapped_dev_nr as usize].is_feature_supported(mapped_sid_nr as i32, feature)
+    }
+
+    pub fn get_buffer_fill(&mut self, dev_nr: i32) -> Option<f32> {
+        let mapped_dev_nr = self.map_device(dev_nr);
+        let mapped_sid_nr = self.map_sid_offset(dev_nr);
+        self.sid_devices[mapped_dev_nr as usize].get_buffer_fill(mapped_sid_nr as i32)
+    }
 }
@@ -15,6 +15,7 @@ pub const MAX_CYCLES_IN_BUFFER: u32 = 63*312 * 50 * 2; // ~2 seconds
 
 const PAL_CYCLES_PER_MICRO: f64 = 17_734_475.0 / 18.0 / 1_000_000.0;
 const NTSC_CYCLES_PER_MICRO: f64 = 14_318_180.0 / 14.0 / 1_000_000.0;
+const DREAN_CYCLES_PER_MICRO: f64 = 14_328_225.0 / 14.0 / 1_000_000.0;
 const ONE_MHZ_CYCLES_PER_MICRO: f64 = 1.0;
 
 const MAX_DEVICE_BUFFER_SIZE: usize = 50;
@@ -22,11 +23,14 @@ const MAX_DEVICE_BUFFER_CYCLES: u32 = 1000;
 const ALLOW_DOUBLE_REG_WRITES_WITHIN_CYCLES: u32 = 20;
 const THRESHOLD_TO_FLUSH_BUFFER_IN_CYCLES: u32 = 500;
 const THRESHOLD_TO_SLEEP_THREAD_IN_MICROS: u64 = 1500;
+const THRESHOLD_TIMING_JUMP_IN_MICROS: u64 = 500_000;
 
 pub enum SidClock {
     Pal = 0,
     Ntsc = 1,
-    OneMhz = 2
+    OneMhz = 2,
+    NtscOld = 3,
+    Drean = 4
 }
 
 impl SidClock {
@@ -34,6 +38,8 @@ impl SidClock {
         match value {
             0 => SidClock::Pal,
             1 => SidClock::Ntsc,
+            3 => SidClock::NtscOld,
+            4 => SidClock::Drean,
             _ => SidClock::OneMhz,
         }
     }
@@ -50,6 +56,7 @@ pub struct SidWrite {
 pub struct SidBlasterScheduler {
     queue: Arc<AtomicRingBuffer<SidWrite>>,
     queue_started: Arc<AtomicBool>,
+    flush_requested: Arc<AtomicBool>,
     cycles_in_buffer: Arc<AtomicU32>,
     sid_writer_thread: Option<thread::JoinHandle<()>>,
     aborted: Arc<AtomicBool>,
@@ -65,6 +72,7 @@ impl SidBlasterScheduler {
     pub fn new(
         queue: Arc<AtomicRingBuffer<SidWrite>>,
         queue_started: Arc<AtomicBool>,
+        flush_requested: Arc<AtomicBool>,
         aborted: Arc<AtomicBool>,
         cycles_in_buffer: Arc<AtomicU32>
     ) -> SidBlasterScheduler {
@@ -72,6 +80,7 @@ impl SidBlasterScheduler {
         SidBlasterScheduler {
             queue,
             queue_started,
+            flush_requested,
             cycles_in_buffer,
             sid_writer_thread: None,
             aborted
@@ -86,12 +95,12 @@ impl SidBlasterScheduler {
         }
     }
 
-    pub fn start(&mut self) -> Result<(), String> {
+    pub fn start(&mut self, latency_millis: u64) -> Result<(), String> {
         self.stop_sid_writer_thread();
 
         self.aborted.store(false, Ordering::SeqCst);
 
-        let mut sid_devices = sidblaster::get_devices()?;
+        let mut sid_devices = sidblaster::get_devices(latency_millis)?;
         if sid_devices.is_empty() {
             return Err(sidblaster::ERROR_MSG_NO_SIDBLASTER_FOUND.to_string());
         }
@@ -99,6 +108,7 @@ impl SidBlasterScheduler {
         let queue = self.queue.clone();
         let cycles_in_buffer = self.cycles_in_buffer.clone();
         let queue_started = self.queue_started.clone();
+        let flush_requested = self.flush_requested.clone();
 
         let aborted = self.aborted.clone();
 
@@ -126,6 +136,15 @@ impl SidBlasterScheduler {
                     break;
                 }
 
+                if flush_requested.swap(false, Ordering::SeqCst) {
+                    next_write = None;
+                    buffer.clear();
+                    cycles_in_temp_buffer = 0;
+                    sid_write_usage = [false; 256];
+                    last_write = None;
+                    cycles_processed = 0;
+                }
+
                 if !queue_started.load(Ordering::SeqCst) {
                     thread::sleep(Duration::from_millis(5));
                     continue;
@@ -140,7 +159,8 @@ impl SidBlasterScheduler {
                 if let Some(sid_write) = &sid_write {
                     let cycles_per_micro = match SidClock::from_u8(sid_write.clock) {
                         SidClock::Pal => PAL_CYCLES_PER_MICRO,
-                        SidClock::Ntsc => NTSC_CYCLES_PER_MICRO,
+                        SidClock::Ntsc | SidClock::NtscOld => NTSC_CYCLES_PER_MICRO,
+                        SidClock::Drean => DREAN_CYCLES_PER_MICRO,
                         SidClock::OneMhz => ONE_MHZ_CYCLES_PER_MICRO
                     };
 
@@ -170,12 +190,16 @@ impl SidBlasterScheduler {
                         }
                     }
 
-                    if !buffer.is_empty() && (should_flush || buffer.len() > MAX_DEVICE_BUFFER_SIZE || cycles_in_temp_buffer > MAX_DEVICE_BUFFER_CYCLES || sid_write.cycles > THRESHOLD_TO_FLUSH_BUFFER_IN_CYCLES) {
+                    if !buffer.is_empty() && !flush_requested.load(Ordering::SeqCst) && (should_flush || buffer.len() > MAX_DEVICE_BUFFER_SIZE || cycles_in_temp_buffer > MAX_DEVICE_BUFFER_CYCLES || sid_write.cycles > THRESHOLD_TO_FLUSH_BUFFER_IN_CYCLES) {
                         if last_write.is_none() {
                             last_write = Some(Instant::now());
                         }
 
-                        Self::wait(cycles_processed, &last_write.unwrap(), cycles_per_micro);
+                        if !Self::wait(cycles_processed, &last_write.unwrap(), cycles_per_micro) {
+                            // the clock jumped ahead, e.g. after a system suspend, so restart the timing instead of catching up
+                            last_write = Some(Instant::now());
+                            cycles_processed = 0;
+                        }
 
                         if sidblaster::write(&mut sid_devices[dev_nr as usize], &buffer).is_err() {
                             aborted.store(true, Ordering::SeqCst);
@@ -209,10 +233,14 @@ impl SidBlasterScheduler {
         Ok(())
     }
 
-    fn wait(cycles: u32, start_time: &Instant, cycles_per_micro: f64) {
+    fn wait(cycles: u32, start_time: &Instant, cycles_per_micro: f64) -> bool {
         let next_time_in_micros = (cycles as f64 / cycles_per_micro) as u64;
         let elapsed_in_micros = start_time.elapsed().as_micros() as u64;
 
+        if elapsed_in_micros > next_time_in_micros + THRESHOLD_TIMING_JUMP_IN_MICROS {
+            return false;
+        }
+
         if elapsed_in_micros < next_time_in_micros {
             let time_to_wait = next_time_in_micros - elapsed_in_micros;
             if time_to_wait > THRESHOLD_TO_SLEEP_THREAD_IN_MICROS {
@@ -221,6 +249,7 @@ impl SidBlasterScheduler {
             let time_to_wait = Duration::from_micros(next_time_in_micros);
             while start_time.elapsed() < time_to_wait {}
         }
+        true
     }
 
     fn is_aborted(aborted: &Arc<AtomicBool>) -> bool {
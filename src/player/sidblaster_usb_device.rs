@@ -2,11 +2,11 @@
 // Licensed under the GNU GPL v3 license. See the LICENSE file for the terms and conditions.
 
 use super::clock_adjust::ClockAdjust;
-use super::sid_device::{DeviceId, DeviceInfo, DeviceResponse, SamplingMethod, SidClock, SidDevice, SidModel};
+use super::sid_device::{DeviceFeature, DeviceId, DeviceInfo, DeviceResponse, SamplingMethod, SidClock, SidDevice, SidModel};
 use super::sidblaster_scheduler::{SidBlasterScheduler, SidWrite, SID_WRITES_BUFFER_SIZE, MAX_CYCLES_IN_BUFFER};
 use super::{ABORT_NO, MIN_CYCLE_SID_WRITE};
 use crate::player::ABORTED;
-use crate::utils::{armsid, armsid::SidFilter, fpgasid, sidblaster};
+use crate::utils::{armsid, armsid::SidFilter, error_log, fpgasid, sidblaster};
 
 use std::sync::atomic::{Ordering, AtomicI32, AtomicU32, AtomicBool};
 use std::sync::Arc;
@@ -106,6 +106,10 @@ impl SidDevice for SidBlasterUsbDeviceFacade {
         self.sb_device.reset_all_buffers();
     }
 
+    fn clear_all_buffers(&mut self, _dev_nr: i32) {
+        self.sb_device.clear_all_buffers();
+    }
+
     fn enable_turbo_mode(&mut self, _dev_nr: i32) {
         self.sb_device.enable_turbo_mode();
     }
@@ -157,6 +161,14 @@ impl SidDevice for SidBlasterUsbDeviceFacade {
     fn set_cycles_in_fifo(&mut self, _dev_nr: i32, _cycles: u32) {
         // not supported
     }
+
+    fn is_feature_supported(&mut self, _dev_nr: i32, feature: DeviceFeature) -> bool {
+        feature != DeviceFeature::FadeIn
+    }
+
+    fn get_buffer_fill(&mut self, _dev_nr: i32) -> Option<f32> {
+        Some(self.sb_device.get_buffer_fill())
+    }
 }
 
 pub struct SidBlasterUsbDevice {
@@ -168,12 +180,14 @@ pub struct SidBlasterUsbDevice {
     abort_type: Arc<AtomicI32>,
     last_error: Option<String>,
     use_native_device_clock: bool,
+    mono_downmix: bool,
     clock_adjust: ClockAdjust,
     cycles_to_compensate: u32,
     sid_blaster_scheduler: SidBlasterScheduler,
     queue: Arc<AtomicRingBuffer<SidWrite>>,
     cycles_in_buffer: Arc<AtomicU32>,
     queue_started: Arc<AtomicBool>,
+    flush_requested: Arc<AtomicBool>,
     last_cycles: u32,
     last_reg: u8,
     last_data: u8,
@@ -185,11 +199,13 @@ impl SidBlasterUsbDevice {
         let cycles_in_buffer = Arc::new(AtomicU32::new(0));
         let buf = Arc::new(AtomicRingBuffer::<SidWrite>::with_capacity(SID_WRITES_BUFFER_SIZE));
         let queue_started = Arc::new(AtomicBool::new(false));
+        let flush_requested = Arc::new(AtomicBool::new(false));
         let aborted = Arc::new(AtomicBool::new(false));
 
         let sid_blaster_scheduler = SidBlasterScheduler::new(
             buf.clone(),
             queue_started.clone(),
+            flush_requested.clone(),
             aborted.clone(),
             cycles_in_buffer.clone()
         );
@@ -203,12 +219,14 @@ impl SidBlasterUsbDevice {
             abort_type,
             last_error: None,
             use_native_device_clock: true,
+            mono_downmix: false,
             clock_adjust: ClockAdjust::new(),
             cycles_to_compensate: 0,
             sid_blaster_scheduler,
             queue: buf,
             cycles_in_buffer,
             queue_started,
+            flush_requested,
             last_cycles: 0,
             last_reg: 0,
             last_data: 0,
@@ -216,7 +234,7 @@ impl SidBlasterUsbDevice {
         }
     }
 
-    pub fn connect(&mut self) -> Result<(), String> {
+    pub fn connect(&mut self, latency_millis: u64) -> Result<(), String> {
         self.disconnect();
 
         self.abort_type.store(ABORT_NO, Ordering::Relaxed);
@@ -227,7 +245,7 @@ impl SidBlasterUsbDevice {
         self.sid_count = self.device_names.len() as i32;
 
         if self.sid_count > 0 {
-            self.sid_blaster_scheduler.start()
+            self.sid_blaster_scheduler.start(latency_millis)
         } else {
             self.last_error = Some(sidblaster::ERROR_MSG_NO_SIDBLASTER_FOUND.to_string());
             Err(sidblaster::ERROR_MSG_NO_SIDBLASTER_FOUND.to_string())
@@ -258,10 +276,15 @@ impl SidBlasterUsbDevice {
     }
 
     fn disconnect_with_error(&mut self, error_message: String) {
+        error_log::write(&error_message);
         self.last_error = Some(error_message);
         self.disconnect();
     }
 
+    pub fn get_buffer_fill(&self) -> f32 {
+        (self.cycles_in_buffer.load(Ordering::SeqCst) as f32 / MAX_CYCLES_IN_BUFFER as f32).min(1.0)
+    }
+
     pub fn get_last_error(&self) -> Option<String> {
         self.last_error.clone()
     }
@@ -469,6 +492,23 @@ impl SidBlasterUsbDevice {
         }
     }
 
+    pub fn clear_all_buffers(&mut self) {
+        if self.is_connected() {
+            self.flush_requested.store(true, Ordering::SeqCst);
+            self.queue.clear();
+            self.cycles_in_buffer.store(0, Ordering::SeqCst);
+
+            let _ = self.queue.try_push(SidWrite {
+                reg: 0,
+                data: 0,
+                cycles: 0,
+                clock: self.get_device_clock() as u8,
+                stop_draining: true
+            });
+            self.start_draining();
+        }
+    }
+
     pub fn enable_turbo_mode(&mut self) {
         self.turbo_mode = true;
     }
@@ -495,6 +535,10 @@ impl SidBlasterUsbDevice {
         self.use_native_device_clock = enabled;
     }
 
+    pub fn set_mono_downmix(&mut self, mono_downmix: bool) {
+        self.mono_downmix = mono_downmix;
+    }
+
     pub fn get_device_clock(&self) -> SidClock {
         if self.use_native_device_clock {
             SidClock::OneMhz
@@ -509,8 +553,13 @@ impl SidBlasterUsbDevice {
 
     fn filter_reg_for_unsupported_writes(&mut self, reg: u8) -> u8 {
         if self.number_of_sids > 1 && !self.are_multiple_sid_chips_supported(reg) {
-            // ignore second/third SID chip for devices that don't support accessing multiple SID chip simultaneously
-            DUMMY_REG
+            if self.mono_downmix {
+                // mix the second/third SID chip into the first SID chip, voices of the SID chips overwrite each other
+                reg & 0x1f
+            } else {
+                // ignore second/third SID chip for devices that don't support accessing multiple SID chip simultaneously
+                DUMMY_REG
+            }
         } else {
             reg
         }
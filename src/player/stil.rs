@@ -40,6 +40,24 @@ impl Stil {
             }).or(global_entries)
     }
 
+    pub fn get_song_titles(stil_entry: &str) -> Vec<(i32, String)> {
+        let mut song_titles = vec![];
+        let mut song_number = -1;
+
+        for line in stil_entry.lines().map(|line| line.trim()) {
+            if let Some(tune_number) = line.strip_prefix("(#").and_then(|line| line.strip_suffix(')')) {
+                song_number = tune_number.parse::<i32>().map_or(-1, |number| number - 1);
+            } else if let Some(title) = line.strip_prefix("TITLE:").or_else(|| line.strip_prefix("NAME:")) {
+                song_titles.push((song_number, title.trim().to_string()));
+            }
+        }
+        song_titles
+    }
+
+    pub fn is_loaded(&self) -> bool {
+        !self.stil_info.is_empty()
+    }
+
     pub fn load(&mut self, hvsc_path_or_stil_file: &str) -> Result<(), String> {
         let hvsc_path = PathBuf::from(hvsc_path_or_stil_file);
         let stil_file = if !hvsc_path.is_file() {
@@ -0,0 +1,226 @@
+// Copyright (C) 2024 Wilfred Bos
+// Licensed under the GNU GPL v3 license. See the LICENSE file for the terms and conditions.
+
+use super::sid_device::{DeviceFeature, DeviceId, DeviceInfo, DeviceResponse, SamplingMethod, SidClock, SidDevice, SidModel};
+
+const SECONDARY_DEVICE_NUMBER: i32 = 0;
+
+// the secondary device follows the timing of the primary device, so both must use a compatible clock
+pub struct TeeSidDevice {
+    primary: Box<dyn SidDevice + Send>,
+    secondary: Box<dyn SidDevice + Send>,
+    primary_busy: bool,
+    secondary_busy: bool
+}
+
+impl TeeSidDevice {
+    pub fn new(primary: Box<dyn SidDevice + Send>, secondary: Box<dyn SidDevice + Send>) -> TeeSidDevice {
+        TeeSidDevice {
+            primary,
+            secondary,
+            primary_busy: false,
+            secondary_busy: false
+        }
+    }
+
+    fn get_response(&self, primary_response: DeviceResponse) -> DeviceResponse {
+        if primary_response == DeviceResponse::Error {
+            DeviceResponse::Error
+        } else if self.primary_busy || self.secondary_busy {
+            DeviceResponse::Busy
+        } else {
+            DeviceResponse::Ok
+        }
+    }
+}
+
+impl SidDevice for TeeSidDevice {
+    fn get_device_id(&mut self, dev_nr: i32) -> DeviceId { self.primary.get_device_id(dev_nr) }
+
+    fn disconnect(&mut self, dev_nr: i32) {
+        self.primary.disconnect(dev_nr);
+        self.secondary.disconnect(SECONDARY_DEVICE_NUMBER);
+    }
+
+    fn is_connected(&mut self, dev_nr: i32) -> bool {
+        self.primary.is_connected(dev_nr)
+    }
+
+    fn get_last_error(&mut self, dev_nr: i32) -> Option<String> {
+        self.primary.get_last_error(dev_nr)
+    }
+
+    fn test_connection(&mut self, dev_nr: i32) {
+        self.primary.test_connection(dev_nr);
+        self.secondary.test_connection(SECONDARY_DEVICE_NUMBER);
+    }
+
+    fn can_pair_devices(&mut self, dev1: i32, dev2: i32) -> bool {
+        self.primary.can_pair_devices(dev1, dev2)
+    }
+
+    fn get_device_count(&mut self, dev_nr: i32) -> i32 {
+        self.primary.get_device_count(dev_nr)
+    }
+
+    fn get_device_info(&mut self, dev_nr: i32) -> DeviceInfo {
+        self.primary.get_device_info(dev_nr)
+    }
+
+    fn set_sid_count(&mut self, dev_nr: i32, sid_count: i32) {
+        self.primary.set_sid_count(dev_nr, sid_count);
+        self.secondary.set_sid_count(SECONDARY_DEVICE_NUMBER, sid_count);
+    }
+
+    fn set_sid_position(&mut self, dev_nr: i32, sid_position: i8) {
+        self.primary.set_sid_position(dev_nr, sid_position);
+        self.secondary.set_sid_position(SECONDARY_DEVICE_NUMBER, sid_position);
+    }
+
+    fn set_sid_model(&mut self, dev_nr: i32, sid_socket: i32, sid_model: SidModel) {
+        self.primary.set_sid_model(dev_nr, sid_socket, sid_model);
+        self.secondary.set_sid_model(SECONDARY_DEVICE_NUMBER, sid_socket, sid_model);
+    }
+
+    fn set_sid_clock(&mut self, dev_nr: i32, sid_clock: SidClock) {
+        self.primary.set_sid_clock(dev_nr, sid_clock);
+        self.secondary.set_sid_clock(SECONDARY_DEVICE_NUMBER, sid_clock);
+    }
+
+    fn set_sampling_method(&mut self, dev_nr: i32, sampling_method: SamplingMethod) {
+        self.primary.set_sampling_method(dev_nr, sampling_method);
+        self.secondary.set_sampling_method(SECONDARY_DEVICE_NUMBER, sampling_method);
+    }
+
+    fn set_sid_header(&mut self, dev_nr: i32, sid_header: Vec<u8>) {
+        self.primary.set_sid_header(dev_nr, sid_header.clone());
+        self.secondary.set_sid_header(SECONDARY_DEVICE_NUMBER, sid_header);
+    }
+
+    fn set_fade_in(&mut self, dev_nr: i32, time_millis: u32) {
+        self.primary.set_fade_in(dev_nr, time_millis);
+        self.secondary.set_fade_in(SECONDARY_DEVICE_NUMBER, time_millis);
+    }
+
+    fn set_fade_out(&mut self, dev_nr: i32, time_millis: u32) {
+        self.primary.set_fade_out(dev_nr, time_millis);
+        self.secondary.set_fade_out(SECONDARY_DEVICE_NUMBER, time_millis);
+    }
+
+    fn silent_all_sids(&mut self, dev_nr: i32, write_volume: bool) {
+        self.primary.silent_all_sids(dev_nr, write_volume);
+        self.secondary.silent_all_sids(SECONDARY_DEVICE_NUMBER, write_volume);
+    }
+
+    fn silent_active_sids(&mut self, dev_nr: i32, write_volume: bool) {
+        self.primary.silent_active_sids(dev_nr, write_volume);
+        self.secondary.silent_active_sids(SECONDARY_DEVICE_NUMBER, write_volume);
+    }
+
+    fn reset_all_sids(&mut self, dev_nr: i32) {
+        self.primary.reset_all_sids(dev_nr);
+        self.secondary.reset_all_sids(SECONDARY_DEVICE_NUMBER);
+    }
+
+    fn reset_active_sids(&mut self, dev_nr: i32) {
+        self.primary.reset_active_sids(dev_nr);
+        self.secondary.reset_active_sids(SECONDARY_DEVICE_NUMBER);
+    }
+
+    fn reset_all_buffers(&mut self, dev_nr: i32) {
+        self.primary_busy = false;
+        self.secondary_busy = false;
+        self.primary.reset_all_buffers(dev_nr);
+        self.secondary.reset_all_buffers(SECONDARY_DEVICE_NUMBER);
+    }
+
+    fn clear_all_buffers(&mut self, dev_nr: i32) {
+        self.primary_busy = false;
+        self.secondary_busy = false;
+        self.primary.clear_all_buffers(dev_nr);
+        self.secondary.clear_all_buffers(SECONDARY_DEVICE_NUMBER);
+    }
+
+    fn enable_turbo_mode(&mut self, dev_nr: i32) {
+        self.primary.enable_turbo_mode(dev_nr);
+        self.secondary.enable_turbo_mode(SECONDARY_DEVICE_NUMBER);
+    }
+
+    fn disable_turbo_mode(&mut self, dev_nr: i32) {
+        self.primary.disable_turbo_mode(dev_nr);
+        self.secondary.disable_turbo_mode(SECONDARY_DEVICE_NUMBER);
+    }
+
+    fn dummy_write(&mut self, dev_nr: i32, cycles: u32) {
+        self.primary.dummy_write(dev_nr, cycles);
+        self.secondary.dummy_write(SECONDARY_DEVICE_NUMBER, cycles);
+    }
+
+    fn write(&mut self, dev_nr: i32, cycles: u32, reg: u8, data: u8) -> DeviceResponse {
+        let response = self.primary.write(dev_nr, cycles, reg, data);
+        self.secondary.write(SECONDARY_DEVICE_NUMBER, cycles, reg, data);
+        response
+    }
+
+    fn try_write(&mut self, dev_nr: i32, cycles: u32, reg: u8, data: u8) -> DeviceResponse {
+        let primary_response = self.primary.try_write(dev_nr, cycles, reg, data);
+        self.primary_busy = primary_response == DeviceResponse::Busy;
+        self.secondary_busy = self.secondary.try_write(SECONDARY_DEVICE_NUMBER, cycles, reg, data) == DeviceResponse::Busy;
+
+        self.get_response(primary_response)
+    }
+
+    fn retry_write(&mut self, dev_nr: i32) -> DeviceResponse {
+        let mut primary_response = DeviceResponse::Ok;
+
+        if self.primary_busy {
+            primary_response = self.primary.retry_write(dev_nr);
+            self.primary_busy = primary_response == DeviceResponse::Busy;
+        }
+
+        if self.secondary_busy {
+            self.secondary_busy = self.secondary.retry_write(SECONDARY_DEVICE_NUMBER) == DeviceResponse::Busy;
+        }
+
+        self.get_response(primary_response)
+    }
+
+    fn force_flush(&mut self, dev_nr: i32) {
+        self.primary.force_flush(dev_nr);
+        self.secondary.force_flush(SECONDARY_DEVICE_NUMBER);
+    }
+
+    fn set_native_device_clock(&mut self, enabled: bool) {
+        self.primary.set_native_device_clock(enabled);
+        self.secondary.set_native_device_clock(enabled);
+    }
+
+    fn get_device_clock(&mut self, dev_nr: i32) -> SidClock {
+        self.primary.get_device_clock(dev_nr)
+    }
+
+    fn has_remote_sidplayer(&mut self, dev_nr: i32) -> bool {
+        self.primary.has_remote_sidplayer(dev_nr)
+    }
+
+    fn send_sid(&mut self, dev_nr: i32, filename: &str, song_number: i32, sid_data: &[u8], ssl_data: &[u8]) {
+        self.primary.send_sid(dev_nr, filename, song_number, sid_data, ssl_data);
+    }
+
+    fn stop_sid(&mut self, dev_nr: i32) {
+        self.primary.stop_sid(dev_nr);
+    }
+
+    fn set_cycles_in_fifo(&mut self, dev_nr: i32, cycles: u32) {
+        self.primary.set_cycles_in_fifo(dev_nr, cycles);
+        self.secondary.set_cycles_in_fifo(SECONDARY_DEVICE_NUMBER, cycles);
+    }
+
+    fn is_feature_supported(&mut self, dev_nr: i32, feature: DeviceFeature) -> bool {
+        self.primary.is_feature_supported(dev_nr, feature)
+    }
+
+    fn get_buffer_fill(&mut self, dev_nr: i32) -> Option<f32> {
+        self.primary.get_buffer_fill(dev_nr)
+    }
+}
@@ -7,9 +7,9 @@ use std::path::Path;
 use std::time::Instant;
 use attohttpc::{Error, Multipart, MultipartBuilder, MultipartFile, Response};
 
-use crate::utils::network;
+use crate::utils::{error_log, network};
 use crate::utils::sid_file::{is_sid_file, FLAG_8580, FLAG_BUILTIN_MUS_PLAYER, FLAG_NTSC, SID_DEFAULT_SONG_OFFSET, SID_FILE_FORMAT_VERSION_OFFSET, SID_FLAGS_OFFSET, SID_HEADER_SIZE, SID_HEADER_SIZE_OFFSET, SID_SONG_COUNT_OFFSET, SID_TITLE_OFFSET};
-use super::sid_device::{DeviceId, DeviceInfo, DeviceResponse, SamplingMethod, SidClock, SidDevice, SidModel};
+use super::sid_device::{DeviceFeature, DeviceId, DeviceInfo, DeviceResponse, SamplingMethod, SidClock, SidDevice, SidModel};
 
 const TOTAL_TIMEOUT: u64 = 5000;
 const CONNECTION_TIMEOUT: u64 = 500;
@@ -117,6 +117,10 @@ impl SidDevice for UltimateDeviceFacade {
         // not supported
     }
 
+    fn clear_all_buffers(&mut self, _dev_nr: i32) {
+        // not supported
+    }
+
     fn enable_turbo_mode(&mut self, _dev_nr: i32) {
         // not supported
     }
@@ -170,6 +174,14 @@ impl SidDevice for UltimateDeviceFacade {
     fn set_cycles_in_fifo(&mut self, _dev_nr: i32, cycles: u32) {
         self.us_device.set_cycles_in_fifo(cycles);
     }
+
+    fn is_feature_supported(&mut self, _dev_nr: i32, feature: DeviceFeature) -> bool {
+        feature != DeviceFeature::FadeIn
+    }
+
+    fn get_buffer_fill(&mut self, _dev_nr: i32) -> Option<f32> {
+        None
+    }
 }
 
 pub struct UltimateDevice {
@@ -246,6 +258,7 @@ impl UltimateDevice {
     }
 
     fn disconnect_with_error(&mut self, error_message: String) {
+        error_log::write(&error_message);
         self.last_error = Some(error_message);
         self.disconnect();
     }
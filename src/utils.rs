@@ -2,10 +2,25 @@
 // Licensed under the GNU GPL v3 license. See the LICENSE file for the terms and conditions.
 
 pub mod armsid;
+pub mod config_file;
+pub mod download;
+pub mod error_log;
 pub mod file;
 pub mod fpgasid;
 pub mod hvsc;
+pub mod json;
 pub mod keyboard;
 pub mod network;
+pub mod petscii;
+pub mod profile;
+pub mod reglog;
+pub mod released;
 pub mod sid_file;
 pub mod sidblaster;
+pub mod song_number;
+#[cfg(unix)]
+pub mod status_socket;
+pub mod term;
+#[cfg(test)]
+pub mod temp_dir;
+pub mod time_position;
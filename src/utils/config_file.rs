@@ -0,0 +1,95 @@
+// Copyright (C) 2024 Wilfred Bos
+// Licensed under the GNU GPL v3 license. See the LICENSE file for the terms and conditions.
+
+use std::env;
+use std::fs;
+use std::path::PathBuf;
+
+const CONFIG_DIR_NAME: &str = "acid64c";
+const CONFIG_FILE_NAME: &str = "acid64c.conf";
+
+/// Reads the default options from the config file and returns them as command line arguments.
+/// Each line contains an option as `name = value`, where the name is a long option without
+/// the dashes or one of the names host, ultimate-host, hvsc, devices and adjust-clock.
+/// Options without a value are enabled with `true`. A missing config file gives no arguments.
+pub fn read_arguments() -> Result<Vec<String>, String> {
+    let Some(config_file) = get_config_file().filter(|config_file| config_file.is_file()) else {
+        return Ok(vec![]);
+    };
+
+    let content = fs::read_to_string(&config_file)
+        .map_err(|error| format!("Config file '{}' could not be read -> {error}", config_file.display()))?;
+
+    let mut arguments = vec![];
+    for (line_number, line) in content.lines().enumerate() {
+        let line = line.trim();
+        if line.is_empty() || line.starts_with('#') {
+            continue;
+        }
+
+        let (name, value) = line.split_once('=')
+            .map(|(name, value)| (name.trim(), value.trim()))
+            .ok_or(format!("Config file '{}' line {}: option must be specified as name = value.", config_file.display(), line_number + 1))?;
+
+        arguments.extend(convert_option(name, value));
+    }
+    Ok(arguments)
+}
+
+/// Updates the value of an option in the config file, or adds the option when it's missing.
+/// Nothing is written when there is no config file.
+pub fn update_value(name: &str, value: &str) -> Result<(), String> {
+    let Some(config_file) = get_config_file().filter(|config_file| config_file.is_file()) else {
+        return Ok(());
+    };
+
+    let content = fs::read_to_string(&config_file)
+        .map_err(|error| format!("Config file '{}' could not be read -> {error}", config_file.display()))?;
+
+    let option_line = format!("{name} = {value}");
+    let mut updated = false;
+    let mut lines = content.lines().map(|line| {
+        if line.split_once('=').is_some_and(|(line_name, _)| line_name.trim() == name) {
+            updated = true;
+            option_line.clone()
+        } else {
+            line.to_string()
+        }
+    }).collect::<Vec<String>>();
+
+    if !updated {
+        lines.push(option_line);
+    }
+
+    fs::write(&config_file, lines.join("\n") + "\n")
+        .map_err(|error| format!("Config file '{}' could not be written -> {error}", config_file.display()))
+}
+
+fn convert_option(name: &str, value: &str) -> Vec<String> {
+    match (name, value) {
+        (_, "false") => vec![],
+        ("host", _) => vec![format!("-hs{value}")],
+        ("ultimate-host", _) => vec![format!("-hu{value}")],
+        ("hvsc", _) => vec![format!("-l{value}")],
+        ("devices", _) => vec![format!("-d{value}")],
+        ("adjust-clock", _) => vec!["-c".to_string()],
+        (_, "true") => vec![format!("--{name}")],
+        _ => vec![format!("--{name}"), value.to_string()]
+    }
+}
+
+fn get_config_file() -> Option<PathBuf> {
+    Some(get_config_dir()?.join(CONFIG_DIR_NAME).join(CONFIG_FILE_NAME))
+}
+
+#[cfg(windows)]
+fn get_config_dir() -> Option<PathBuf> {
+    env::var_os("APPDATA").map(PathBuf::from)
+}
+
+#[cfg(not(windows))]
+fn get_config_dir() -> Option<PathBuf> {
+    env::var_os("XDG_CONFIG_HOME")
+        .map(PathBuf::from)
+        .or_else(|| env::var_os("HOME").map(|home| PathBuf::from(home).join(".config")))
+}
@@ -0,0 +1,105 @@
+// Copyright (C) 2024 Wilfred Bos
+// Licensed under the GNU GPL v3 license. See the LICENSE file for the terms and conditions.
+
+use std::env;
+use std::fs::{self, OpenOptions};
+use std::io::Write;
+use std::path::PathBuf;
+use std::process;
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+
+const DOWNLOAD_FOLDER: &str = "acid64c";
+const DEFAULT_DOWNLOAD_FILENAME: &str = "download.sid";
+const CONNECTION_TIMEOUT: u64 = 5000;
+const TOTAL_TIMEOUT: u64 = 30000;
+
+pub struct DownloadedFile {
+    path: PathBuf
+}
+
+impl DownloadedFile {
+    pub fn get_filename(&self) -> String {
+        self.path.to_string_lossy().to_string()
+    }
+}
+
+impl Drop for DownloadedFile {
+    fn drop(&mut self) {
+        let _ = fs::remove_file(&self.path);
+    }
+}
+
+pub fn is_url(filename: &str) -> bool {
+    let filename = filename.to_ascii_lowercase();
+    filename.starts_with("http://") || filename.starts_with("https://")
+}
+
+pub fn download_file(url: &str) -> Result<DownloadedFile, String> {
+    let response = attohttpc::get(url)
+        .timeout(Duration::from_millis(TOTAL_TIMEOUT))
+        .connect_timeout(Duration::from_millis(CONNECTION_TIMEOUT))
+        .send()
+        .map_err(|error| format!("Could not download {url} -> {error}"))?;
+
+    if !response.is_success() {
+        return Err(format!("Could not download {url} -> HTTP status {}", response.status().as_u16()));
+    }
+
+    let data = response.bytes().map_err(|error| format!("Could not download {url} -> {error}"))?;
+
+    let download_folder = env::temp_dir().join(DOWNLOAD_FOLDER);
+    fs::create_dir_all(&download_folder).map_err(|error| format!("Could not create folder {} -> {error}", download_folder.to_string_lossy()))?;
+
+    // the file name of the URL is only kept for its extension and readability, a unique prefix avoids clashes with other downloads
+    let unique_id = SystemTime::now().duration_since(UNIX_EPOCH).map_or(0, |time| time.as_nanos());
+    let path = download_folder.join(format!("{}_{unique_id}_{}", process::id(), get_filename_from_url(url)));
+
+    let mut file = OpenOptions::new().write(true).create_new(true).open(&path)
+        .map_err(|error| format!("Could not create file {} -> {error}", path.to_string_lossy()))?;
+    let downloaded_file = DownloadedFile { path };
+
+    file.write_all(&data).map_err(|error| format!("Could not write file {} -> {error}", downloaded_file.get_filename()))?;
+    Ok(downloaded_file)
+}
+
+fn get_filename_from_url(url: &str) -> String {
+    let path = url.split(['?', '#']).next().unwrap_or_default();
+    let path = path.split_once("://").map_or(path, |(_, path)| path);
+
+    path.split_once('/')
+        .and_then(|(_, path)| path.rsplit('/').next())
+        .map(|filename| filename.chars().filter(|c| c.is_ascii_alphanumeric() || ['.', '-', '_'].contains(c)).collect::<String>())
+        .map(|filename| filename.trim_start_matches('.').to_string())
+        .filter(|filename| !filename.is_empty())
+        .unwrap_or(DEFAULT_DOWNLOAD_FILENAME.to_string())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn detects_url_scheme() {
+        assert!(is_url("https://example.com/tune.sid"));
+        assert!(is_url("HTTP://example.com/tune.sid"));
+        assert!(!is_url("C:\\C64Music\\tune.sid"));
+    }
+
+    #[test]
+    fn uses_last_path_segment_as_filename() {
+        assert_eq!(get_filename_from_url("https://example.com/music/Commando.sid?download=1#top"), "Commando.sid");
+    }
+
+    #[test]
+    fn uses_default_filename_without_path() {
+        assert_eq!(get_filename_from_url("https://example.com"), DEFAULT_DOWNLOAD_FILENAME);
+        assert_eq!(get_filename_from_url("https://example.com/music/"), DEFAULT_DOWNLOAD_FILENAME);
+    }
+
+    #[test]
+    fn strips_path_traversal_from_filename() {
+        assert_eq!(get_filename_from_url("https://example.com/..%2F..%2Fevil.sid"), "2F..2Fevil.sid");
+        assert_eq!(get_filename_from_url("https://example.com/..\\..\\evil.sid"), "evil.sid");
+        assert_eq!(get_filename_from_url("https://example.com/.."), DEFAULT_DOWNLOAD_FILENAME);
+    }
+}
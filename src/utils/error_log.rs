@@ -0,0 +1,26 @@
+// Copyright (C) 2024 Wilfred Bos
+// Licensed under the GNU GPL v3 license. See the LICENSE file for the terms and conditions.
+
+use std::fs::{File, OpenOptions};
+use std::io::Write;
+use std::sync::Mutex;
+
+static ERROR_LOG: Mutex<Option<File>> = Mutex::new(None);
+
+/// Opens the error log in append mode, errors written before opening the log are ignored.
+pub fn open(filename: &str) -> Result<(), String> {
+    let file = OpenOptions::new().create(true).append(true).open(filename)
+        .map_err(|error| format!("Error log '{filename}' could not be opened: {error}"))?;
+
+    *ERROR_LOG.lock().unwrap() = Some(file);
+    Ok(())
+}
+
+/// Appends the error with a timestamp to the error log when the log is open.
+pub fn write(error: &str) {
+    if let Some(file) = ERROR_LOG.lock().unwrap().as_mut() {
+        let timestamp = chrono::Local::now().format("%Y-%m-%d %H:%M:%S");
+        let _ = writeln!(file, "{timestamp} {error}");
+        let _ = file.flush();
+    }
+}
@@ -2,9 +2,9 @@
 // Licensed under the GNU GPL v3 license. See the LICENSE file for the terms and conditions.
 
 #![allow(dead_code)]
-use std::fs::File;
+use std::fs::{self, File};
 use std::io::{self, BufRead, BufReader, Error};
-use std::path::PathBuf;
+use std::path::{Path, PathBuf};
 use encoding_rs::WINDOWS_1252;
 use encoding_rs_io::DecodeReaderBytesBuilder;
 
@@ -14,6 +14,37 @@ pub fn read_buffer_as_lines(buffer: &[u8]) -> impl Iterator<Item = io::Result<St
         .build(buffer)).lines()
 }
 
+pub fn get_sorted_files_in_directory(directory: &Path) -> Vec<PathBuf> {
+    let mut files = fs::read_dir(directory)
+        .map(|entries| entries
+            .filter_map(|entry| entry.ok().map(|entry| entry.path()))
+            .filter(|path| path.is_file())
+            .collect::<Vec<_>>())
+        .unwrap_or_default();
+
+    files.sort_by_key(|path| path.file_name().map(|name| name.to_string_lossy().to_lowercase()));
+    files
+}
+
+// symbolic links to directories are skipped, since a link to a parent directory would recurse endlessly
+pub fn get_sorted_files_in_directory_tree(directory: &Path) -> Vec<PathBuf> {
+    let mut sub_directories = fs::read_dir(directory)
+        .map(|entries| entries
+            .filter_map(|entry| entry.ok())
+            .filter(|entry| entry.file_type().is_ok_and(|file_type| file_type.is_dir()))
+            .map(|entry| entry.path())
+            .collect::<Vec<_>>())
+        .unwrap_or_default();
+
+    sub_directories.sort_by_key(|path| path.file_name().map(|name| name.to_string_lossy().to_lowercase()));
+
+    let mut files = get_sorted_files_in_directory(directory);
+    for sub_directory in sub_directories {
+        files.extend(get_sorted_files_in_directory_tree(&sub_directory));
+    }
+    files
+}
+
 pub fn read_text_file_as_lines(config_path: &PathBuf, max_file_size: Option<u64>) -> Result<impl Iterator<Item = io::Result<String>>, String> {
     let lines = read_lines(config_path, max_file_size);
     lines.map_err(|error| format!("Error reading file: {} -> {}", config_path.display(), error))
@@ -31,3 +62,37 @@ fn read_lines(filename: &PathBuf, max_file_size: Option<u64>) -> io::Result<impl
         .encoding(Some(WINDOWS_1252))
         .build(file)).lines())
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::utils::temp_dir::TempDir;
+
+    fn create_directory_tree(name: &str) -> TempDir {
+        let temp_dir = TempDir::new(&format!("file_{name}"));
+        temp_dir.create_file("b/second.sid");
+        temp_dir.create_file("A/first.sid");
+        temp_dir.create_file("root.sid");
+        temp_dir
+    }
+
+    fn get_file_names(temp_dir: &TempDir) -> Vec<String> {
+        get_sorted_files_in_directory_tree(temp_dir.path()).iter()
+            .map(|path| path.strip_prefix(temp_dir.path()).unwrap().to_string_lossy().replace('\\', "/"))
+            .collect()
+    }
+
+    #[test]
+    fn lists_files_before_sub_directories_sorted_by_name() {
+        let temp_dir = create_directory_tree("sorted");
+        assert_eq!(get_file_names(&temp_dir), vec!["root.sid", "A/first.sid", "b/second.sid"]);
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn skips_symbolic_links_to_directories() {
+        let temp_dir = create_directory_tree("symlink");
+        std::os::unix::fs::symlink(temp_dir.path(), temp_dir.path().join("A").join("loop")).unwrap();
+        assert_eq!(get_file_names(&temp_dir), vec!["root.sid", "A/first.sid", "b/second.sid"]);
+    }
+}
@@ -39,3 +39,88 @@ pub fn get_hvsc_filename(filename: &str) -> String {
     let filename = filename.rsplit_once(hvsc_root.as_ref().unwrap()).unwrap().1;
     filename.replace('\\', "/")
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::utils::temp_dir::TempDir;
+
+    struct HvscFixture {
+        temp_dir: TempDir
+    }
+
+    impl HvscFixture {
+        fn new(name: &str) -> HvscFixture {
+            let temp_dir = TempDir::new(&format!("hvsc_{name}"));
+            temp_dir.create_file("C64Music/MUSICIANS/H/Hubbard_Rob/Commando.sid");
+            temp_dir.create_file("C64Music/DOCUMENTS/STIL.txt");
+            HvscFixture { temp_dir }
+        }
+
+        fn base_dir(&self) -> &Path {
+            self.temp_dir.path()
+        }
+
+        fn hvsc_root(&self) -> String {
+            self.base_dir().join("C64Music").to_str().unwrap().to_string()
+        }
+
+        fn tune(&self) -> String {
+            self.base_dir().join("C64Music").join("MUSICIANS").join("H").join("Hubbard_Rob").join("Commando.sid").to_str().unwrap().to_string()
+        }
+    }
+
+    #[test]
+    fn finds_root_from_file_inside_hvsc() {
+        let fixture = HvscFixture::new("file");
+        assert_eq!(get_hvsc_root(&fixture.tune()), Some(fixture.hvsc_root()));
+    }
+
+    #[test]
+    fn finds_root_from_directory_inside_hvsc() {
+        let fixture = HvscFixture::new("dir");
+        let musicians_dir = Path::new(&fixture.hvsc_root()).join("MUSICIANS").to_str().unwrap().to_string();
+        assert_eq!(get_hvsc_root(&musicians_dir), Some(fixture.hvsc_root()));
+    }
+
+    #[test]
+    fn finds_root_when_specified_directly() {
+        let fixture = HvscFixture::new("root");
+        assert_eq!(get_hvsc_root(&fixture.hvsc_root()), Some(fixture.hvsc_root()));
+    }
+
+    #[test]
+    fn finds_root_from_parent_of_c64music() {
+        let fixture = HvscFixture::new("parent");
+        assert_eq!(get_hvsc_root(fixture.base_dir().to_str().unwrap()), Some(fixture.hvsc_root()));
+    }
+
+    #[test]
+    fn finds_root_from_documents_directory() {
+        let fixture = HvscFixture::new("documents");
+        let documents_dir = Path::new(&fixture.hvsc_root()).join("DOCUMENTS").to_str().unwrap().to_string();
+        assert_eq!(get_hvsc_root(&documents_dir), Some(fixture.hvsc_root()));
+    }
+
+    #[test]
+    fn returns_none_outside_hvsc() {
+        let temp_dir = TempDir::new("hvsc_outside");
+        let tune = temp_dir.create_file("Commando.sid");
+        assert_eq!(get_hvsc_root(tune.to_str().unwrap()), None);
+    }
+
+    #[test]
+    fn converts_filename_to_hvsc_path() {
+        let fixture = HvscFixture::new("filename");
+        assert_eq!(get_hvsc_filename(&fixture.tune()), "/MUSICIANS/H/Hubbard_Rob/Commando.sid");
+    }
+
+    #[cfg(windows)]
+    #[test]
+    fn converts_drive_letter_path_to_hvsc_path() {
+        let fixture = HvscFixture::new("drive");
+        let tune = fixture.tune();
+        assert!(tune.chars().nth(1) == Some(':') && tune.contains('\\'));
+        assert_eq!(get_hvsc_filename(&tune), "/MUSICIANS/H/Hubbard_Rob/Commando.sid");
+    }
+}
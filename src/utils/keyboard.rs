@@ -3,6 +3,7 @@
 
 use crossterm::event::{poll, read, Event, KeyCode, KeyEvent, KeyEventKind};
 use std::time::Duration;
+use crate::utils::song_number::{self, DEFAULT_SONG};
 
 pub const ESC_KEY: char = '\x1b';
 pub const LEFT_KEY: char = '\x25';
@@ -18,14 +19,19 @@ pub fn get_char_from_input() -> Option<char> {
 
 pub fn convert_num_key_to_number(key: char) -> i32 {
     match key {
-        '1' ..= '9' => key as i32 - '1' as i32,
-        '0' => 9,
-        _ => -1
+        '1' ..= '9' => song_number::from_user_song_number(key as i32 - '0' as i32).unwrap_or(DEFAULT_SONG),
+        '0' => song_number::from_user_song_number(10).unwrap_or(DEFAULT_SONG),
+        _ => DEFAULT_SONG
     }
 }
 
 fn read_char() -> Option<char> {
-    if let Ok(Event::Key(KeyEvent{ code, kind, .. })) = read() {
+    read().ok().and_then(convert_event_to_char)
+}
+
+// crossterm decodes escape sequences, so an arrow key arrives as a single key event and is never mistaken for ESC
+fn convert_event_to_char(event: Event) -> Option<char> {
+    if let Event::Key(KeyEvent{ code, kind, .. }) = event {
         if kind == KeyEventKind::Press {
             match code {
                 KeyCode::Char(c) => return Some(c),
@@ -38,3 +44,41 @@ fn read_char() -> Option<char> {
     }
     None
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crossterm::event::KeyModifiers;
+
+    fn key_event(code: KeyCode, kind: KeyEventKind) -> Event {
+        Event::Key(KeyEvent::new_with_kind(code, KeyModifiers::NONE, kind))
+    }
+
+    #[test]
+    fn converts_escape_and_arrow_keys() {
+        assert_eq!(convert_event_to_char(key_event(KeyCode::Esc, KeyEventKind::Press)), Some(ESC_KEY));
+        assert_eq!(convert_event_to_char(key_event(KeyCode::Left, KeyEventKind::Press)), Some(LEFT_KEY));
+        assert_eq!(convert_event_to_char(key_event(KeyCode::Right, KeyEventKind::Press)), Some(RIGHT_KEY));
+    }
+
+    #[test]
+    fn converts_characters() {
+        assert_eq!(convert_event_to_char(key_event(KeyCode::Char('p'), KeyEventKind::Press)), Some('p'));
+        assert_eq!(convert_event_to_char(key_event(KeyCode::Char('+'), KeyEventKind::Press)), Some('+'));
+    }
+
+    #[test]
+    fn ignores_releases_and_other_events() {
+        assert_eq!(convert_event_to_char(key_event(KeyCode::Esc, KeyEventKind::Release)), None);
+        assert_eq!(convert_event_to_char(key_event(KeyCode::Up, KeyEventKind::Press)), None);
+        assert_eq!(convert_event_to_char(Event::FocusGained), None);
+    }
+
+    #[test]
+    fn converts_number_keys_to_song_numbers() {
+        assert_eq!(convert_num_key_to_number('1'), 0);
+        assert_eq!(convert_num_key_to_number('9'), 8);
+        assert_eq!(convert_num_key_to_number('0'), 9);
+        assert_eq!(convert_num_key_to_number('a'), DEFAULT_SONG);
+    }
+}
@@ -0,0 +1,31 @@
+// Copyright (C) 2024 Wilfred Bos
+// Licensed under the GNU GPL v3 license. See the LICENSE file for the terms and conditions.
+
+#![allow(dead_code)]
+
+pub fn to_char(petscii: u8) -> char {
+    match petscii {
+        0x20..=0x40 => petscii as char,
+        0x41..=0x5a => (petscii + 0x20) as char,
+        0x5b => '[',
+        0x5c => '£',
+        0x5d => ']',
+        0x5e => '↑',
+        0x5f => '←',
+        0x61..=0x7a => (petscii - 0x20) as char,
+        0xc1..=0xda => (petscii - 0x80) as char,
+        _ => ' '
+    }
+}
+
+pub fn decode_lines(petscii_text: &[u8], line_length: usize) -> Vec<String> {
+    let mut lines: Vec<String> = petscii_text
+        .chunks(line_length)
+        .map(|line| line.iter().map(|&petscii| to_char(petscii)).collect::<String>().trim_end().to_string())
+        .collect();
+
+    while lines.last().is_some_and(|line| line.is_empty()) {
+        lines.pop();
+    }
+    lines
+}
@@ -0,0 +1,22 @@
+// Copyright (C) 2024 Wilfred Bos
+// Licensed under the GNU GPL v3 license. See the LICENSE file for the terms and conditions.
+
+use std::time::Instant;
+
+pub struct PhaseTimer {
+    start: Option<Instant>
+}
+
+impl PhaseTimer {
+    pub fn start(enabled: bool) -> PhaseTimer {
+        PhaseTimer {
+            start: enabled.then(Instant::now)
+        }
+    }
+
+    pub fn stop(self, phase: &str) {
+        if let Some(start) = self.start {
+            eprintln!("profile: {phase:<16} {:>9.3} ms", start.elapsed().as_secs_f64() * 1000.0);
+        }
+    }
+}
@@ -0,0 +1,160 @@
+// Copyright (C) 2024 Wilfred Bos
+// Licensed under the GNU GPL v3 license. See the LICENSE file for the terms and conditions.
+
+//! Register log format, one entry per line:
+//!
+//! ```text
+//! ACID64 REGLOG 1
+//! SIDS <number_of_sids>
+//! MODELS <model of SID 1> .. <model of SID n>    (0 = unknown, 1 = 6581, 2 = 8580, 3 = both)
+//! CLOCK <PAL|NTSC>
+//! SONG <song_number>
+//! W <cycles> <reg> <data>                        (cycles since previous write, reg and data in hex)
+//! E <NEXTPART|SEEKDONE>
+//! ```
+//!
+//! The register number includes the SID offset, so register $38 is the volume register of SID 2.
+
+#![allow(dead_code)]
+use std::fs::File;
+use std::io::{BufRead, BufReader, BufWriter, Lines, Write};
+
+pub const REGLOG_ID: &str = "ACID64 REGLOG 1";
+
+pub struct RegisterLogHeader {
+    pub number_of_sids: i32,
+    pub sid_models: Vec<i32>,
+    pub ntsc: bool
+}
+
+pub struct RegisterLog {
+    writer: Option<BufWriter<File>>,
+    filename: String
+}
+
+impl RegisterLog {
+    pub fn create(filename: &str, header: &RegisterLogHeader) -> Result<RegisterLog, String> {
+        let file = File::create(filename).map_err(|error| format!("Register log '{filename}' could not be created: {error}"))?;
+
+        let mut register_log = RegisterLog {
+            writer: Some(BufWriter::new(file)),
+            filename: filename.to_string()
+        };
+
+        let models = header.sid_models.iter().map(|model| model.to_string()).collect::<Vec<_>>().join(" ");
+        let clock = if header.ntsc { "NTSC" } else { "PAL" };
+
+        register_log.write_line(&format!("{REGLOG_ID}\nSIDS {}\nMODELS {models}\nCLOCK {clock}", header.number_of_sids));
+        Ok(register_log)
+    }
+
+    pub fn write(&mut self, cycles: u32, reg: u8, data: u8) {
+        self.write_line(&format!("W {cycles} {reg:02x} {data:02x}"));
+    }
+
+    pub fn song(&mut self, song_number: i32) {
+        self.write_line(&format!("SONG {}", song_number + 1));
+    }
+
+    pub fn event(&mut self, event: &str) {
+        self.write_line(&format!("E {event}"));
+    }
+
+    pub fn get_filename(&self) -> &str {
+        &self.filename
+    }
+
+    fn write_line(&mut self, line: &str) {
+        if let Some(writer) = self.writer.as_mut() {
+            if writeln!(writer, "{line}").is_err() {
+                self.writer = None;
+            }
+        }
+    }
+}
+
+impl Drop for RegisterLog {
+    fn drop(&mut self) {
+        if let Some(writer) = self.writer.as_mut() {
+            let _ = writer.flush();
+        }
+    }
+}
+
+pub struct RegisterLogSource {
+    lines: Lines<BufReader<File>>,
+    header: RegisterLogHeader,
+    line_number: usize
+}
+
+impl RegisterLogSource {
+    pub fn open(filename: &str) -> Result<RegisterLogSource, String> {
+        let file = File::open(filename).map_err(|error| format!("Register log '{filename}' could not be opened: {error}"))?;
+
+        let mut register_log_source = RegisterLogSource {
+            lines: BufReader::new(file).lines(),
+            header: RegisterLogHeader { number_of_sids: 1, sid_models: vec![], ntsc: false },
+            line_number: 0
+        };
+
+        if register_log_source.read_line()?.as_deref() != Some(REGLOG_ID) {
+            return Err(format!("File '{filename}' is not a register log."));
+        }
+
+        register_log_source.read_header()?;
+        Ok(register_log_source)
+    }
+
+    pub fn get_header(&self) -> &RegisterLogHeader {
+        &self.header
+    }
+
+    pub fn next_write(&mut self) -> Result<Option<(u32, u8, u8)>, String> {
+        while let Some(line) = self.read_line()? {
+            let mut fields = line.split_whitespace();
+
+            if fields.next() == Some("W") {
+                let cycles = fields.next().and_then(|cycles| cycles.parse::<u32>().ok());
+                let reg = fields.next().and_then(|reg| u8::from_str_radix(reg, 16).ok());
+                let data = fields.next().and_then(|data| u8::from_str_radix(data, 16).ok());
+
+                return match (cycles, reg, data) {
+                    (Some(cycles), Some(reg), Some(data)) => Ok(Some((cycles, reg, data))),
+                    _ => Err(self.invalid_line_error())
+                };
+            }
+        }
+        Ok(None)
+    }
+
+    fn read_header(&mut self) -> Result<(), String> {
+        for _ in 0..3 {
+            let line = self.read_line()?.ok_or_else(|| self.invalid_line_error())?;
+
+            match line.split_once(' ') {
+                Some(("SIDS", value)) => {
+                    self.header.number_of_sids = value.parse::<i32>().ok()
+                        .filter(|number_of_sids| (1..=8).contains(number_of_sids))
+                        .ok_or_else(|| self.invalid_line_error())?;
+                },
+                Some(("MODELS", value)) => {
+                    self.header.sid_models = value.split_whitespace()
+                        .map(|model| model.parse::<i32>().map_err(|_| self.invalid_line_error()))
+                        .collect::<Result<Vec<i32>, String>>()?;
+                },
+                Some(("CLOCK", value)) => self.header.ntsc = value == "NTSC",
+                _ => return Err(self.invalid_line_error())
+            }
+        }
+        Ok(())
+    }
+
+    fn read_line(&mut self) -> Result<Option<String>, String> {
+        self.line_number += 1;
+        self.lines.next().transpose().map_err(|error| format!("Register log could not be read: {error}"))
+    }
+
+    fn invalid_line_error(&self) -> String {
+        format!("Invalid register log entry at line {}.", self.line_number)
+    }
+}
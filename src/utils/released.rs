@@ -0,0 +1,75 @@
+// Copyright (C) 2024 Wilfred Bos
+// Licensed under the GNU GPL v3 license. See the LICENSE file for the terms and conditions.
+
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub struct ReleaseYear {
+    pub first: u16,
+    pub last: u16
+}
+
+/// Parses the year or year range at the start of a released field, like "1987", "1985-1986",
+/// "1987-88 Rob Hubbard" or "198?". Unknown digits widen the range, so "19??" becomes 1900-1999.
+pub fn parse_release_year(released: &str) -> Option<ReleaseYear> {
+    let released = released.trim_start();
+    let (first, last) = parse_year(released.get(0..4)?)?;
+
+    let mut release_year = ReleaseYear { first, last };
+
+    if let Some(end_year) = released[4..].strip_prefix('-') {
+        let digits = end_year.chars().take_while(|c| c.is_ascii_digit() || *c == '?').count();
+        let end_year = match digits {
+            2 => parse_year(&format!("{}{}", &released[0..2], &end_year[0..2])),
+            4 => parse_year(&end_year[0..4]),
+            _ => None
+        };
+
+        if let Some((_, last)) = end_year {
+            if last >= release_year.first {
+                release_year.last = last;
+            }
+        }
+    } else if released[4..].starts_with(|c: char| c.is_ascii_alphanumeric()) {
+        return None;
+    }
+
+    Some(release_year)
+}
+
+fn parse_year(year: &str) -> Option<(u16, u16)> {
+    if year.len() != 4 || !year.starts_with(|c: char| c.is_ascii_digit()) || !year.chars().all(|c| c.is_ascii_digit() || c == '?') {
+        return None;
+    }
+
+    let first = year.replace('?', "0").parse::<u16>().ok()?;
+    let last = year.replace('?', "9").parse::<u16>().ok()?;
+    Some((first, last))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parses_single_year() {
+        assert_eq!(parse_release_year("1985 Hewson"), Some(ReleaseYear { first: 1985, last: 1985 }));
+    }
+
+    #[test]
+    fn parses_year_ranges() {
+        assert_eq!(parse_release_year("1985-1986"), Some(ReleaseYear { first: 1985, last: 1986 }));
+        assert_eq!(parse_release_year("1987-88 Rob Hubbard"), Some(ReleaseYear { first: 1987, last: 1988 }));
+    }
+
+    #[test]
+    fn widens_unknown_digits() {
+        assert_eq!(parse_release_year("19??"), Some(ReleaseYear { first: 1900, last: 1999 }));
+        assert_eq!(parse_release_year("198?"), Some(ReleaseYear { first: 1980, last: 1989 }));
+    }
+
+    #[test]
+    fn rejects_unparseable_values() {
+        assert_eq!(parse_release_year(""), None);
+        assert_eq!(parse_release_year("<?>"), None);
+        assert_eq!(parse_release_year("19850 Hewson"), None);
+    }
+}
@@ -16,7 +16,166 @@ pub const FLAG_NTSC: u8 = 0x08;
 pub const FLAG_8580: u8 = 0x20;
 
 const MIN_SID_HEADER_SIZE: usize = 0x76;
+// a SID file needs at least the version 1 header followed by one byte of C64 data
+const MIN_SID_FILE_SIZE: usize = MIN_SID_HEADER_SIZE + 1;
+// other files, like MUS and PRG files, need at least a load address followed by one byte of C64 data
+const MIN_FILE_SIZE: usize = 3;
 
 pub fn is_sid_file(source: &[u8]) -> bool {
-    source.len() >= MIN_SID_HEADER_SIZE && matches!(&source[0..4], b"RSID" | b"PSID")
+    source.len() >= MIN_SID_HEADER_SIZE && has_sid_id(source)
+}
+
+pub fn has_sid_id(source: &[u8]) -> bool {
+    source.len() >= 4 && matches!(&source[0..4], b"RSID" | b"PSID")
+}
+
+pub fn is_valid_file(source: &[u8]) -> bool {
+    if has_sid_id(source) {
+        source.len() >= MIN_SID_FILE_SIZE && source.len() > source[SID_HEADER_SIZE_OFFSET] as usize
+    } else {
+        source.len() >= MIN_FILE_SIZE
+    }
+}
+
+pub fn get_number_of_songs(source: &[u8]) -> Option<u16> {
+    if !is_sid_file(source) {
+        return None;
+    }
+
+    Some(u16::from_be_bytes([source[SID_SONG_COUNT_OFFSET - 1], source[SID_SONG_COUNT_OFFSET]]))
+}
+
+pub fn get_sid_header(source: &[u8]) -> Option<Vec<u8>> {
+    if !is_sid_file(source) {
+        return None;
+    }
+
+    let header_size = source[SID_HEADER_SIZE_OFFSET] as usize;
+    let mut sid_header = source[0..header_size.clamp(MIN_SID_HEADER_SIZE, source.len())].to_vec();
+
+    if sid_header.len() < SID_HEADER_SIZE {
+        sid_header.resize(SID_HEADER_SIZE, 0);
+    }
+    Some(sid_header)
+}
+
+const SID_HEADER_FIELDS: [(usize, usize, &str); 17] = [
+    (0x00, 4, "magic"),
+    (0x04, 2, "version"),
+    (0x06, 2, "data offset"),
+    (0x08, 2, "load address"),
+    (0x0a, 2, "init address"),
+    (0x0c, 2, "play address"),
+    (0x0e, 2, "songs"),
+    (0x10, 2, "start song"),
+    (0x12, 4, "speed"),
+    (0x16, 32, "name"),
+    (0x36, 32, "author"),
+    (0x56, 32, "released"),
+    (0x76, 2, "flags"),
+    (0x78, 1, "start page"),
+    (0x79, 1, "page length"),
+    (0x7a, 1, "second SID address"),
+    (0x7b, 1, "third SID address")
+];
+
+const HEX_DUMP_BYTES_PER_LINE: usize = 16;
+
+pub fn hexdump_header(source: &[u8]) -> String {
+    let header_size = if is_sid_file(source) {
+        (source[SID_HEADER_SIZE_OFFSET] as usize).clamp(MIN_SID_HEADER_SIZE, source.len())
+    } else {
+        source.len().min(SID_HEADER_SIZE)
+    };
+
+    let mut output = String::new();
+    for (offset, size, label) in SID_HEADER_FIELDS {
+        if offset >= header_size {
+            break;
+        }
+
+        let field = &source[offset..(offset + size).min(header_size)];
+        for (line_number, bytes) in field.chunks(HEX_DUMP_BYTES_PER_LINE).enumerate() {
+            let hex_bytes = bytes.iter().map(|byte| format!("{byte:02X}")).collect::<Vec<String>>().join(" ");
+            let label = if line_number == 0 { label } else { "" };
+            output.push_str(&format!("{:04X}  {hex_bytes:<47}  {label}\n", offset + line_number * HEX_DUMP_BYTES_PER_LINE));
+        }
+    }
+    output
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn create_sid_file(header_size: usize, data_size: usize) -> Vec<u8> {
+        let mut sid_file = vec![0; header_size + data_size];
+        sid_file[0..4].copy_from_slice(b"PSID");
+        sid_file[SID_HEADER_SIZE_OFFSET] = header_size as u8;
+        sid_file
+    }
+
+    #[test]
+    fn rejects_empty_file() {
+        assert!(!is_valid_file(&[]));
+        assert!(!is_sid_file(&[]));
+        assert_eq!(get_sid_header(&[]), None);
+    }
+
+    #[test]
+    fn rejects_truncated_header() {
+        let sid_file = create_sid_file(SID_HEADER_SIZE, 1);
+        let truncated_file = &sid_file[0..MIN_SID_HEADER_SIZE - 1];
+
+        assert!(!is_sid_file(truncated_file));
+        assert!(!is_valid_file(truncated_file));
+        assert_eq!(get_sid_header(truncated_file), None);
+    }
+
+    #[test]
+    fn rejects_sid_file_without_data() {
+        assert!(!is_valid_file(&create_sid_file(MIN_SID_HEADER_SIZE, 0)));
+        assert!(!is_valid_file(&create_sid_file(SID_HEADER_SIZE, 0)));
+    }
+
+    #[test]
+    fn accepts_smallest_sid_files() {
+        assert!(is_valid_file(&create_sid_file(MIN_SID_HEADER_SIZE, 1)));
+        assert!(is_valid_file(&create_sid_file(SID_HEADER_SIZE, 1)));
+    }
+
+    #[test]
+    fn reads_number_of_songs() {
+        let mut sid_file = create_sid_file(SID_HEADER_SIZE, 1);
+        sid_file[SID_SONG_COUNT_OFFSET - 1] = 0x01;
+        sid_file[SID_SONG_COUNT_OFFSET] = 0x02;
+
+        assert_eq!(get_number_of_songs(&sid_file), Some(0x0102));
+        assert_eq!(get_number_of_songs(&[0x01, 0x08, 0x00]), None);
+    }
+
+    #[test]
+    fn reads_zero_songs() {
+        assert_eq!(get_number_of_songs(&create_sid_file(SID_HEADER_SIZE, 1)), Some(0));
+    }
+
+    #[test]
+    fn pads_version_1_header() {
+        let sid_header = get_sid_header(&create_sid_file(MIN_SID_HEADER_SIZE, 1)).unwrap();
+        assert_eq!(sid_header.len(), SID_HEADER_SIZE);
+    }
+
+    #[test]
+    fn handles_garbage_files() {
+        assert!(!is_valid_file(&[0xff, 0x00]));
+        assert!(is_valid_file(&[0x01, 0x08, 0x00]));
+        assert!(!is_sid_file(&[0xff; 0x100]));
+        assert!(!has_sid_id(b"PSI"));
+    }
+
+    #[test]
+    fn hexdump_of_truncated_file_does_not_panic() {
+        assert_eq!(hexdump_header(&[]), "");
+        assert!(hexdump_header(b"PSID").starts_with("0000  50 53 49 44"));
+    }
 }
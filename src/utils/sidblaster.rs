@@ -6,7 +6,7 @@ use std::time::{Duration, Instant};
 use libftd2xx::{BitsPerWord, Ftdi, FtdiCommon, FtStatus, list_devices, Parity, StopBits};
 
 const BAUD_RATE: u32 = 500_000;
-const LATENCY_IN_MILLIS: u64 = 2;
+pub const DEFAULT_LATENCY_IN_MILLIS: u64 = 2;
 const DEVICE_TIME_OUT_IN_MILLIS: u64 = 1000;
 const LOOP_TIME_OUT_MILLIS: u64 = 250;
 const ERROR_MSG_DEVICE_FAILURE: &str = "Failed to communicate with FTDI device.";
@@ -17,10 +17,10 @@ pub fn detect_devices() -> Result<Vec<(String, String)>, String> {
     get_device_names(&serials)
 }
 
-pub fn get_devices() -> Result<Vec<Ftdi>, String> {
+pub fn get_devices(latency_millis: u64) -> Result<Vec<Ftdi>, String> {
     get_serials()?.iter().map(|serial| {
         let mut usb_device = Ftdi::with_serial_number(serial).map_err(|_| ERROR_MSG_DEVICE_FAILURE.to_string())?;
-        configure_device(&mut usb_device).map_err(|_| ERROR_MSG_DEVICE_FAILURE.to_string())?;
+        configure_device(&mut usb_device, latency_millis).map_err(|_| ERROR_MSG_DEVICE_FAILURE.to_string())?;
         Ok(usb_device)
     }).collect::<Result<Vec<_>, _>>()
 }
@@ -53,12 +53,12 @@ fn get_device_names(serials: &[String]) -> Result<Vec<(String, String)>, String>
     }).collect()
 }
 
-fn configure_device(usb_device: &mut Ftdi) -> Result<(), FtStatus> {
+fn configure_device(usb_device: &mut Ftdi, latency_millis: u64) -> Result<(), FtStatus> {
     usb_device.set_baud_rate(BAUD_RATE)?;
     usb_device.set_data_characteristics(BitsPerWord::Bits8, StopBits::Bits1, Parity::No)?;
     usb_device.set_break_off()?;
     usb_device.set_flow_control_none()?;
-    usb_device.set_latency_timer(Duration::from_millis(LATENCY_IN_MILLIS))?;
+    usb_device.set_latency_timer(Duration::from_millis(latency_millis))?;
     usb_device.set_timeouts(Duration::from_millis(DEVICE_TIME_OUT_IN_MILLIS), Duration::from_millis(DEVICE_TIME_OUT_IN_MILLIS))
 }
 
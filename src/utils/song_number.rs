@@ -0,0 +1,112 @@
+// Copyright (C) 2024 Wilfred Bos
+// Licensed under the GNU GPL v3 license. See the LICENSE file for the terms and conditions.
+
+// song numbers are 1-based for the user and 0-based in the player and library
+
+/// Song number that selects the default song of the file.
+pub const DEFAULT_SONG: i32 = -1;
+
+/// Converts a 1-based song number given by the user, returns None when the number is lower than 1.
+pub fn from_user_song_number(user_song_number: i32) -> Option<i32> {
+    (user_song_number >= 1).then_some(user_song_number - 1)
+}
+
+/// Converts a 0-based song number to the 1-based song number shown to the user.
+pub fn to_user_song_number(song_number: i32) -> i32 {
+    song_number + 1
+}
+
+/// Returns the song after the given song within the range, wrapping around to the first song.
+pub fn get_next_song_in_range(song_number: i32, (first_song, last_song): (i32, i32)) -> i32 {
+    if song_number >= last_song || song_number < first_song {
+        first_song
+    } else {
+        song_number + 1
+    }
+}
+
+/// Returns the song before the given song within the range, wrapping around to the last song.
+pub fn get_prev_song_in_range(song_number: i32, (first_song, last_song): (i32, i32)) -> i32 {
+    if song_number <= first_song || song_number > last_song {
+        last_song
+    } else {
+        song_number - 1
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const MAX_SONG_NUMBER: i32 = 256;
+
+    #[test]
+    fn rejects_user_song_numbers_below_one() {
+        assert_eq!(from_user_song_number(0), None);
+        assert_eq!(from_user_song_number(DEFAULT_SONG), None);
+    }
+
+    #[test]
+    fn default_song_is_not_a_user_song_number() {
+        assert!((1..=MAX_SONG_NUMBER).all(|user_song_number| from_user_song_number(user_song_number) != Some(DEFAULT_SONG)));
+    }
+
+    #[test]
+    fn converts_first_song() {
+        assert_eq!(from_user_song_number(1), Some(0));
+        assert_eq!(to_user_song_number(0), 1);
+    }
+
+    #[test]
+    fn converts_max_song() {
+        assert_eq!(from_user_song_number(MAX_SONG_NUMBER), Some(MAX_SONG_NUMBER - 1));
+        assert_eq!(to_user_song_number(MAX_SONG_NUMBER - 1), MAX_SONG_NUMBER);
+    }
+
+    #[test]
+    fn converts_user_song_numbers_back_and_forth() {
+        for user_song_number in 1..=MAX_SONG_NUMBER {
+            assert_eq!(from_user_song_number(user_song_number).map(to_user_song_number), Some(user_song_number));
+        }
+    }
+
+    #[test]
+    fn next_song_stays_on_single_song() {
+        assert_eq!(get_next_song_in_range(0, (0, 0)), 0);
+    }
+
+    #[test]
+    fn prev_song_stays_on_single_song() {
+        assert_eq!(get_prev_song_in_range(0, (0, 0)), 0);
+    }
+
+    #[test]
+    fn navigates_two_songs() {
+        assert_eq!(get_next_song_in_range(0, (0, 1)), 1);
+        assert_eq!(get_next_song_in_range(1, (0, 1)), 0);
+        assert_eq!(get_prev_song_in_range(0, (0, 1)), 1);
+        assert_eq!(get_prev_song_in_range(1, (0, 1)), 0);
+    }
+
+    #[test]
+    fn wraps_around_many_songs() {
+        assert_eq!(get_next_song_in_range(5, (0, 31)), 6);
+        assert_eq!(get_next_song_in_range(31, (0, 31)), 0);
+        assert_eq!(get_prev_song_in_range(5, (0, 31)), 4);
+        assert_eq!(get_prev_song_in_range(0, (0, 31)), 31);
+    }
+
+    #[test]
+    fn wraps_around_song_range() {
+        assert_eq!(get_next_song_in_range(6, (2, 6)), 2);
+        assert_eq!(get_prev_song_in_range(2, (2, 6)), 6);
+    }
+
+    #[test]
+    fn moves_song_outside_range_into_range() {
+        assert_eq!(get_next_song_in_range(0, (2, 6)), 2);
+        assert_eq!(get_next_song_in_range(9, (2, 6)), 2);
+        assert_eq!(get_prev_song_in_range(0, (2, 6)), 6);
+        assert_eq!(get_prev_song_in_range(9, (2, 6)), 6);
+    }
+}
@@ -0,0 +1,26 @@
+// Copyright (C) 2024 Wilfred Bos
+// Licensed under the GNU GPL v3 license. See the LICENSE file for the terms and conditions.
+
+use std::os::unix::net::UnixDatagram;
+
+pub struct StatusSocket {
+    socket: UnixDatagram,
+    path: String
+}
+
+impl StatusSocket {
+    pub fn new(path: &str) -> Result<StatusSocket, String> {
+        let socket = UnixDatagram::unbound().map_err(|error| format!("Status socket could not be created: {error}"))?;
+        socket.set_nonblocking(true).map_err(|error| format!("Status socket could not be created: {error}"))?;
+
+        Ok(StatusSocket {
+            socket,
+            path: path.to_string()
+        })
+    }
+
+    pub fn send(&self, status: &str) {
+        // a missing or disconnected reader is not an error, the status is sent again on the next update
+        let _ = self.socket.send_to(status.as_bytes(), &self.path);
+    }
+}
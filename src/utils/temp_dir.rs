@@ -0,0 +1,38 @@
+// Copyright (C) 2024 Wilfred Bos
+// Licensed under the GNU GPL v3 license. See the LICENSE file for the terms and conditions.
+
+use std::fs;
+use std::path::{Path, PathBuf};
+
+/// An empty directory in the temp directory for tests, which is removed again when dropped.
+pub struct TempDir {
+    path: PathBuf
+}
+
+impl TempDir {
+    pub fn new(name: &str) -> TempDir {
+        let path = std::env::temp_dir().join(format!("acid64c_{name}_{}", std::process::id()));
+        let _ = fs::remove_dir_all(&path);
+
+        fs::create_dir_all(&path).unwrap();
+        TempDir { path }
+    }
+
+    pub fn path(&self) -> &Path {
+        &self.path
+    }
+
+    /// Creates an empty file, including its parent directories, relative to the temp directory.
+    pub fn create_file(&self, file_name: &str) -> PathBuf {
+        let file = self.path.join(file_name);
+        fs::create_dir_all(file.parent().unwrap()).unwrap();
+        fs::write(&file, "").unwrap();
+        file
+    }
+}
+
+impl Drop for TempDir {
+    fn drop(&mut self) {
+        let _ = fs::remove_dir_all(&self.path);
+    }
+}
@@ -0,0 +1,83 @@
+// Copyright (C) 2024 Wilfred Bos
+// Licensed under the GNU GPL v3 license. See the LICENSE file for the terms and conditions.
+
+#![allow(dead_code)]
+
+use std::env;
+use std::io::{stdout, IsTerminal, Write};
+use std::sync::atomic::{AtomicBool, Ordering};
+
+const NO_COLOR_ENV: &str = "NO_COLOR";
+
+const ANSI_RESET: &str = "\x1b[0m";
+const ANSI_BOLD: &str = "\x1b[1m";
+const ANSI_DIM: &str = "\x1b[2m";
+const ANSI_CYAN: &str = "\x1b[36m";
+const ANSI_YELLOW: &str = "\x1b[33m";
+
+static COLOR_ENABLED: AtomicBool = AtomicBool::new(false);
+
+#[derive(Copy, Clone, Debug, Eq, PartialEq)]
+pub enum ColorMode {
+    Auto,
+    Always,
+    Never
+}
+
+pub fn set_color_mode(color_mode: ColorMode) {
+    let enabled = match color_mode {
+        ColorMode::Always => true,
+        ColorMode::Never => false,
+        ColorMode::Auto => !is_no_color_set() && stdout().is_terminal() && supports_ansi()
+    };
+    COLOR_ENABLED.store(enabled, Ordering::Relaxed);
+}
+
+fn is_no_color_set() -> bool {
+    env::var_os(NO_COLOR_ENV).is_some_and(|value| !value.is_empty())
+}
+
+pub fn is_color_enabled() -> bool {
+    COLOR_ENABLED.load(Ordering::Relaxed)
+}
+
+pub fn bold(text: &str) -> String {
+    apply(ANSI_BOLD, text)
+}
+
+pub fn dim(text: &str) -> String {
+    apply(ANSI_DIM, text)
+}
+
+pub fn cyan(text: &str) -> String {
+    apply(ANSI_CYAN, text)
+}
+
+pub fn yellow(text: &str) -> String {
+    apply(ANSI_YELLOW, text)
+}
+
+fn apply(code: &str, text: &str) -> String {
+    if is_color_enabled() {
+        format!("{code}{text}{ANSI_RESET}")
+    } else {
+        text.to_string()
+    }
+}
+
+pub fn set_window_title(title: &str) {
+    if stdout().is_terminal() && supports_ansi() {
+        print!("\x1b]0;{title}\x07");
+        let _ = stdout().flush();
+    }
+}
+
+#[cfg(windows)]
+fn supports_ansi() -> bool {
+    crossterm::ansi_support::supports_ansi()
+}
+
+#[cfg(not(windows))]
+fn supports_ansi() -> bool {
+    true
+}
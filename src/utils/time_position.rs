@@ -0,0 +1,84 @@
+// Copyright (C) 2024 Wilfred Bos
+// Licensed under the GNU GPL v3 license. See the LICENSE file for the terms and conditions.
+
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub enum TimePosition {
+    Absolute(u32),
+    Percentage(u32),
+    BeforeEnd(u32)
+}
+
+impl TimePosition {
+    /// Parses a time position like "1:30", a percentage of the song length like "50%" or
+    /// an offset before the end of the song like "-0:10".
+    pub fn parse(time_position: &str) -> Option<TimePosition> {
+        if let Some(percentage) = time_position.strip_suffix('%') {
+            let percentage = percentage.parse::<u32>().ok()?;
+            (percentage <= 100).then_some(TimePosition::Percentage(percentage))
+        } else if let Some(time) = time_position.strip_prefix('-') {
+            parse_time(time).map(TimePosition::BeforeEnd)
+        } else {
+            parse_time(time_position).map(TimePosition::Absolute)
+        }
+    }
+
+    pub fn resolve(&self, song_length_in_millis: Option<i32>) -> Result<u32, String> {
+        let song_length = match self {
+            TimePosition::Absolute(time) => return Ok(*time),
+            _ => song_length_in_millis.ok_or("A percentage or a time before the end requires a known song length.")? as u32
+        };
+
+        match self {
+            TimePosition::Percentage(percentage) => Ok((song_length as u64 * *percentage as u64 / 100) as u32),
+            TimePosition::BeforeEnd(time) if *time <= song_length => Ok(song_length - time),
+            _ => Err(format!("Time before the end exceeds the song length of {} seconds.", (song_length + 500) / 1000))
+        }
+    }
+}
+
+fn parse_time(time: &str) -> Option<u32> {
+    let (minutes, seconds) = time.split_once(':')?;
+    let minutes = minutes.parse::<u32>().ok()?;
+    let seconds = seconds.parse::<u32>().ok()?;
+
+    if seconds < 60 {
+        minutes.checked_mul(60)?.checked_add(seconds)?.checked_mul(1000)
+    } else {
+        None
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parses_time_positions() {
+        assert_eq!(TimePosition::parse("1:30"), Some(TimePosition::Absolute(90_000)));
+        assert_eq!(TimePosition::parse("50%"), Some(TimePosition::Percentage(50)));
+        assert_eq!(TimePosition::parse("-0:10"), Some(TimePosition::BeforeEnd(10_000)));
+    }
+
+    #[test]
+    fn rejects_invalid_time_positions() {
+        assert_eq!(TimePosition::parse("1:60"), None);
+        assert_eq!(TimePosition::parse("101%"), None);
+        assert_eq!(TimePosition::parse("90"), None);
+        assert_eq!(TimePosition::parse("99999:00"), None);
+        assert_eq!(TimePosition::parse("-99999:00"), None);
+    }
+
+    #[test]
+    fn resolves_time_positions() {
+        assert_eq!(TimePosition::Absolute(5_000).resolve(None), Ok(5_000));
+        assert_eq!(TimePosition::Percentage(50).resolve(Some(120_000)), Ok(60_000));
+        assert_eq!(TimePosition::BeforeEnd(10_000).resolve(Some(120_000)), Ok(110_000));
+    }
+
+    #[test]
+    fn resolve_fails_without_song_length_or_beyond_it() {
+        assert!(TimePosition::Percentage(50).resolve(None).is_err());
+        assert!(TimePosition::BeforeEnd(10_000).resolve(None).is_err());
+        assert!(TimePosition::BeforeEnd(130_000).resolve(Some(120_000)).is_err());
+    }
+}